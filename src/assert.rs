@@ -0,0 +1,70 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{CaseError, TesterError};
+
+/// Maximum number of characters of actual output quoted in a failure message.
+const MAX_RENDERED_CHARS: usize = 200;
+
+/// Fails unless `haystack` contains `needle`. The failure message starts with `context` and
+/// quotes the actual output.
+pub fn assert_contains(haystack: &[u8], needle: &[u8], context: &str) -> Result<(), CaseError> {
+    if needle.is_empty() || haystack.windows(needle.len()).any(|window| window == needle) {
+        return Ok(());
+    }
+
+    Err(TesterError::assertion(format!(
+        "{context}: expected output to contain {:?}, got {:?}",
+        String::from_utf8_lossy(needle),
+        render(haystack)
+    ))
+    .into())
+}
+
+/// Fails unless `line` equals `expected`, ignoring a trailing `\n` or `\r\n` on `line`. The
+/// failure message starts with `context` and quotes the actual line.
+pub fn assert_line_eq(line: &[u8], expected: &str, context: &str) -> Result<(), CaseError> {
+    let trimmed = line.strip_suffix(b"\n").unwrap_or(line);
+    let trimmed = trimmed.strip_suffix(b"\r").unwrap_or(trimmed);
+    if trimmed == expected.as_bytes() {
+        return Ok(());
+    }
+
+    Err(TesterError::assertion(format!(
+        "{context}: expected line {expected:?}, got {:?}",
+        render(trimmed)
+    ))
+    .into())
+}
+
+/// Converts `\r\n` line endings to `\n`, e.g. to compare output of a program running on
+/// Windows against expectations written with `\n`. A `\r` not followed by `\n` is kept.
+pub fn normalize_newlines(bytes: &[u8]) -> Vec<u8> {
+    let mut normalized = Vec::with_capacity(bytes.len());
+    for (index, &byte) in bytes.iter().enumerate() {
+        if byte != b'\r' || bytes.get(index + 1) != Some(&b'\n') {
+            normalized.push(byte);
+        }
+    }
+    normalized
+}
+
+/// Renders bytes as lossy UTF-8, truncated to [`MAX_RENDERED_CHARS`] characters.
+fn render(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    match text.char_indices().nth(MAX_RENDERED_CHARS) {
+        Some((end, _)) => format!("{}... ({} bytes total)", &text[..end], bytes.len()),
+        None => text.into_owned(),
+    }
+}
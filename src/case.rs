@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use crate::Harness;
-use std::{error::Error, fmt, sync::Arc, time::Duration};
+use std::{collections::HashMap, error::Error, fmt, sync::Arc, time::Duration};
 
 /// A generic error type that can represent any error implementing `std::error::Error`.
 pub type CaseError = Box<dyn Error + Send + Sync>;
@@ -21,6 +21,9 @@ pub type CaseError = Box<dyn Error + Send + Sync>;
 /// A function type representing a test case's logic.
 pub type Function = Arc<dyn Fn(&Harness) -> Result<(), CaseError> + Send + Sync>;
 
+/// A predicate over the run's environment variables that decides whether a test case is skipped.
+pub type SkipPredicate = Arc<dyn Fn(&HashMap<String, String>) -> bool + Send + Sync>;
+
 /// Represents a test case that will be executed against the user's code.
 pub struct Case {
     /// Unique identifier for the test case. Must match the stage's slug.
@@ -31,12 +34,98 @@ pub struct Case {
 
     /// Maximum duration the test case is allowed to run.
     pub timeout: Duration,
+
+    /// Optional function that prepares fixtures before the test logic runs.
+    pub setup: Option<Function>,
+
+    /// Number of times a failing test case is retried before it is reported as failed.
+    pub retries: usize,
+
+    /// Environment variables set on the executable for this test case, on top of its own.
+    pub env: HashMap<String, String>,
+
+    /// Categories used to select a subset of test cases with `STACKCLASS_ONLY_TAGS`.
+    pub tags: Vec<String>,
+
+    /// Slugs of the test cases that must pass before this one is worth running.
+    pub depends_on: Vec<String>,
+
+    /// Optional predicate that skips the test case when it holds, e.g. on unsupported platforms.
+    pub skip_if: Option<SkipPredicate>,
+
+    /// Optional explanation of what the test case verifies, shown when it fails.
+    pub description: Option<String>,
 }
 
 impl Case {
     /// Creates a new `Case` with the given slug and function.
     pub fn new<S: Into<String>>(slug: S, function: Function) -> Self {
-        Self { slug: slug.into(), function, timeout: Duration::from_secs(10) }
+        Self {
+            slug: slug.into(),
+            function,
+            timeout: Duration::from_secs(10),
+            setup: None,
+            retries: 0,
+            env: HashMap::new(),
+            tags: Vec::new(),
+            depends_on: Vec::new(),
+            skip_if: None,
+            description: None,
+        }
+    }
+
+    /// Sets the maximum duration the test case is allowed to run.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets a setup function that runs before the test logic. If it fails, the test case fails
+    /// without running its main function.
+    pub fn with_setup(mut self, setup: Function) -> Self {
+        self.setup = Some(setup);
+        self
+    }
+
+    /// Retries the test case up to `retries` more times if it fails, to tolerate rare races in
+    /// otherwise correct submissions. Teardown functions run after every attempt.
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets environment variables for the executable launched by this test case, e.g. to enable a
+    /// feature flag in a later stage. They override the executable's variables of the same name.
+    pub fn with_env(mut self, vars: HashMap<String, String>) -> Self {
+        self.env = vars;
+        self
+    }
+
+    /// Adds a category tag (e.g., `"network"`) to the test case.
+    pub fn with_tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Adds a dependency on the test case with the given slug. If that case fails in the same
+    /// run, this one is skipped rather than run against state that was never built.
+    pub fn with_dependency<S: Into<String>>(mut self, slug: S) -> Self {
+        self.depends_on.push(slug.into());
+        self
+    }
+
+    /// Skips the test case when `predicate` holds for the run's environment variables. A skipped
+    /// test case is reported as skipped rather than failed, and does not stop the run.
+    pub fn with_skip_if(mut self, predicate: SkipPredicate) -> Self {
+        self.skip_if = Some(predicate);
+        self
+    }
+
+    /// Sets an explanation of what the test case verifies (e.g., `"the server replies to PING"`),
+    /// which is logged when it fails to give learners context.
+    pub fn with_description<S: Into<String>>(mut self, description: S) -> Self {
+        self.description = Some(description.into());
+        self
     }
 
     /// Returns the timeout duration.
@@ -48,6 +137,14 @@ impl Case {
 
 impl fmt::Debug for Case {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Case").field("slug", &self.slug).field("timeout", &self.timeout).finish()
+        f.debug_struct("Case")
+            .field("slug", &self.slug)
+            .field("timeout", &self.timeout)
+            .field("retries", &self.retries)
+            .field("tags", &self.tags)
+            .field("depends_on", &self.depends_on)
+            .field("skip_if", &self.skip_if.is_some())
+            .field("description", &self.description)
+            .finish()
     }
 }
@@ -12,36 +12,111 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{Definition, Tester};
-use std::{collections::HashMap, process::ExitCode};
+use crate::{Definition, OutputFormat, Result, RunReport, Tester, context::parse_bool_env};
+use std::{
+    collections::HashMap,
+    io::{self, IsTerminal},
+    process::ExitCode,
+};
+use tracing::{Level, warn};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
 /// Executes the provided test definition and returns an exit code.
 pub fn run(env: HashMap<String, String>, definition: Definition) -> ExitCode {
-    // Create a new tester instance
-    let tester = match Tester::new(env, definition) {
-        Ok(tester) => tester,
+    init_logging(&env);
+
+    // Return failure if any stage fails or if an error occurs.
+    match run_with_report(env, definition) {
+        Ok(report) if report.passed() => ExitCode::SUCCESS,
+        Ok(_) => ExitCode::FAILURE,
         Err(err) => {
             eprintln!("{err}");
-            return ExitCode::FAILURE;
+            ExitCode::FAILURE
         }
-    };
+    }
+}
+
+/// Executes the provided test definition like [`run`] and returns the outcome of every executed
+/// step, for embedders that render results themselves.
+///
+/// Unlike [`run`], this does not install a log subscriber. When only listing the stages, the
+/// returned report is empty.
+pub fn run_with_report(env: HashMap<String, String>, definition: Definition) -> Result<RunReport> {
+    let tester = Tester::new(env, definition)?;
 
     // Prints the debug context if debugging is enabled.
     tester.print_debug_context();
 
-    // Early exit if validation fails
-    if let Err(err) = tester.validate() {
-        eprintln!("{err}");
-        return ExitCode::FAILURE;
+    tester.validate()?;
+
+    // List the resolved stages without running anything if requested.
+    if tester.is_list_only() {
+        print!("{}", tester.list_steps());
+        return Ok(RunReport::default());
     }
 
-    // Execute test stages. Return failure if any stage fails or if an error occurs.
-    match tester.run() {
-        Ok(true) => ExitCode::SUCCESS,
-        Ok(false) => ExitCode::FAILURE,
-        Err(err) => {
-            eprintln!("{err}");
-            ExitCode::FAILURE
-        }
+    tester.run_report()
+}
+
+/// Installs a global `tracing` subscriber that prints events at the level chosen by
+/// `STACKCLASS_LOG` (`error`, `warn`, `info` or `debug`; default `info`). `STACKCLASS_DEBUG`
+/// is a shortcut for `debug`. Events go to stdout, or to stderr when
+/// `STACKCLASS_OUTPUT_FORMAT=json` reserves stdout for the results. Levels are colored when that
+/// stream is a terminal, unless `NO_COLOR` is set (see [`color_enabled`]), so that the steps a
+/// [`TracingReporter`](crate::TracingReporter) reports as passed, failed and skipped stand out.
+///
+/// Returns false without changing anything if a global subscriber is already installed, so it is
+/// safe to call more than once.
+pub fn init_logging(env: &HashMap<String, String>) -> bool {
+    let requested = env.get("STACKCLASS_LOG").map(|value| (value, parse_level(value)));
+    let is_debug = env.get("STACKCLASS_DEBUG").is_some_and(|v| parse_bool_env(v));
+
+    let mut level = match requested {
+        Some((_, Some(level))) => level,
+        _ => Level::INFO,
+    };
+    if is_debug {
+        level = level.max(Level::DEBUG);
+    }
+
+    // Keep stdout free for the results when they are printed as JSON.
+    let (writer, is_terminal) = match OutputFormat::from_env(env) {
+        OutputFormat::Json => (BoxMakeWriter::new(io::stderr), io::stderr().is_terminal()),
+        OutputFormat::Text => (BoxMakeWriter::new(io::stdout), io::stdout().is_terminal()),
+    };
+    let installed = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_ansi(color_enabled(env, is_terminal))
+        .with_writer(writer)
+        .try_init()
+        .is_ok();
+
+    if installed && let Some((value, None)) = requested {
+        warn!(
+            "Ignoring unknown STACKCLASS_LOG level {value:?}, expected error, warn, info or debug"
+        );
+    }
+
+    installed
+}
+
+/// Returns whether output written to a stream should be colored with ANSI escape codes, given
+/// whether that stream is a terminal.
+///
+/// Following the `NO_COLOR` convention, a non-empty `NO_COLOR` disables colors even on a
+/// terminal.
+pub fn color_enabled(env: &HashMap<String, String>, is_terminal: bool) -> bool {
+    let no_color = env.get("NO_COLOR").is_some_and(|value| !value.is_empty());
+    is_terminal && !no_color
+}
+
+/// Parses a log level name, ignoring case and surrounding whitespace.
+fn parse_level(value: &str) -> Option<Level> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "error" => Some(Level::ERROR),
+        "warn" => Some(Level::WARN),
+        "info" => Some(Level::INFO),
+        "debug" => Some(Level::DEBUG),
+        _ => None,
     }
 }
@@ -12,36 +12,108 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{Definition, Result, TesterError};
+use crate::{Definition, Result, TesterError, executable::is_executable, join_within};
 use serde::Deserialize;
-use std::{collections::HashMap, path::PathBuf, time::Duration};
-use tracing::debug;
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tracing::{debug, warn};
 
 /// Holds all configuration and runtime context for the tester, including
 /// environment variables, test cases, and execution settings.
 #[derive(Debug)]
 pub struct Context {
+    /// Directory holding the submission (controlled by `STACKCLASS_REPOSITORY_DIR`).
+    pub repository_dir: PathBuf,
+
     /// Path to the executable being tested.
     pub executable_path: PathBuf,
 
     /// Whether the tester is running in debug mode (controlled by `STACKCLASS_DEBUG`).
     pub is_debug: bool,
 
-    /// List of test cases to execute, parsed from `STACKCLASS_TEST_CASES_JSON`.
+    /// List of test cases to execute, parsed from `STACKCLASS_TEST_CASES_JSON` or
+    /// `STACKCLASS_TEST_CASES_FILE`.
     pub cases: Vec<ContextCase>,
 
     /// Timeout duration for test execution (default: 15 seconds).
     pub timeout: Duration,
 
+    /// Wall-clock budget for running all stages, if any (controlled by
+    /// `STACKCLASS_TOTAL_TIMEOUT_SECONDS`).
+    pub total_timeout: Option<Duration>,
+
     /// Environment variables passed to the tester.
     pub env: HashMap<String, String>,
 
     /// Whether to skip anti-cheat test cases (controlled by `STACKCLASS_SKIP_ANTI_CHEAT`).
     pub should_skip_anti_cheat: bool,
+
+    /// Format of the results printed after a run (controlled by `STACKCLASS_OUTPUT_FORMAT`).
+    pub output_format: OutputFormat,
+
+    /// Whether to print a per-step timing table after a run (controlled by `STACKCLASS_TIMING`).
+    pub show_timing: bool,
+
+    /// Whether to list the resolved steps instead of running them (controlled by
+    /// `STACKCLASS_LIST_ONLY`).
+    pub list_only: bool,
+
+    /// Whether every non-anti-cheat definition case must be referenced by the context
+    /// (controlled by `STACKCLASS_STRICT_DEFINITION`).
+    pub strict_definition: bool,
+
+    /// Whether context cases without a matching definition case are logged and left out instead
+    /// of failing validation, for platforms that send stages the tester doesn't implement yet
+    /// (controlled by `STACKCLASS_IGNORE_UNKNOWN_CASES`).
+    pub ignore_unknown_cases: bool,
+
+    /// Whether to run the steps ordered by their log prefix, e.g. `stage-2` before `stage-10`,
+    /// rather than in the order they are listed (controlled by `STACKCLASS_SORT_STAGES`).
+    pub sort_stages: bool,
+
+    /// Tags selecting which test cases run; empty runs them all (controlled by the
+    /// comma-separated `STACKCLASS_ONLY_TAGS`).
+    pub only_tags: Vec<String>,
+
+    /// Slug of the only test case to run, e.g. while debugging a flaky stage (controlled by
+    /// `STACKCLASS_ONLY_SLUG`).
+    pub only_slug: Option<String>,
 }
 
-/// Represents a single test case defined in the `STACKCLASS_TEST_CASES_JSON` environment variable.
-#[derive(Debug, Deserialize)]
+/// Format of the results printed once all test steps have run.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable logs only.
+    #[default]
+    Text,
+
+    /// A JSON array of per-step outcomes printed to stdout, with the logs and any other text
+    /// written to stderr so that stdout can be parsed as a whole.
+    Json,
+}
+
+impl OutputFormat {
+    /// Reads the format selected by `STACKCLASS_OUTPUT_FORMAT`.
+    pub(crate) fn from_env(env: &HashMap<String, String>) -> Self {
+        match env.get("STACKCLASS_OUTPUT_FORMAT").map(String::as_str) {
+            Some("json") => OutputFormat::Json,
+            _ => OutputFormat::Text,
+        }
+    }
+}
+
+/// Represents a single test case defined in the `STACKCLASS_TEST_CASES_JSON` environment variable
+/// or in the file named by `STACKCLASS_TEST_CASES_FILE`.
+///
+/// Unknown fields are rejected so that a misspelled key (e.g. `"prefix"`) is reported rather
+/// than silently ignored.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ContextCase {
     /// Unique identifier for the test case (e.g., "bind-to-port").
     pub slug: String,
@@ -55,16 +127,54 @@ pub struct ContextCase {
 
 impl Context {
     pub fn from_env(env: HashMap<String, String>, definition: &Definition) -> Result<Self> {
-        let submission_dir = env
+        let repository_dir = env
             .get("STACKCLASS_REPOSITORY_DIR")
+            .map(PathBuf::from)
             .ok_or(TesterError::MissingEnvVar("STACKCLASS_REPOSITORY_DIR".into()))?;
 
-        let test_cases_json = env
-            .get("STACKCLASS_TEST_CASES_JSON")
-            .ok_or(TesterError::MissingEnvVar("STACKCLASS_TEST_CASES_JSON".into()))?;
+        Self::from_parts(env, repository_dir, definition)
+    }
+
+    /// Like [`Context::from_env`], but accepts the environment as the OS provides it, so that
+    /// `STACKCLASS_REPOSITORY_DIR` may be a path that is not valid UTF-8.
+    ///
+    /// Every other `STACKCLASS_*` variable must be valid UTF-8. Other variables that are not are
+    /// left out of [`Context::env`].
+    pub fn from_env_os(env: HashMap<OsString, OsString>, definition: &Definition) -> Result<Self> {
+        let mut repository_dir = None;
+        let mut utf8_env = HashMap::new();
+
+        for (key, value) in env {
+            let Some(key) = key.to_str().map(String::from) else { continue };
+            if key == "STACKCLASS_REPOSITORY_DIR" {
+                repository_dir = Some(PathBuf::from(&value));
+            }
+
+            match value.into_string() {
+                Ok(value) => {
+                    utf8_env.insert(key, value);
+                }
+                Err(_) if key.starts_with("STACKCLASS_") && key != "STACKCLASS_REPOSITORY_DIR" => {
+                    return Err(TesterError::InvalidConfig(format!("{key} must be valid UTF-8")));
+                }
+                Err(_) => {}
+            }
+        }
+
+        let repository_dir =
+            repository_dir.ok_or(TesterError::MissingEnvVar("STACKCLASS_REPOSITORY_DIR".into()))?;
 
-        let cases: Vec<ContextCase> = serde_json::from_str(test_cases_json)
-            .map_err(|e| TesterError::JsonParse(e.to_string()))?;
+        Self::from_parts(utf8_env, repository_dir, definition)
+    }
+
+    /// Builds the context from the UTF-8 environment and the already extracted submission
+    /// directory.
+    fn from_parts(
+        env: HashMap<String, String>,
+        repository_dir: PathBuf,
+        definition: &Definition,
+    ) -> Result<Self> {
+        let cases = Self::load_cases(&env)?;
 
         // Validate test cases
         for case in &cases {
@@ -85,36 +195,171 @@ impl Context {
             return Err(TesterError::InvalidTestCase("No test cases provided".into()));
         }
 
-        let executable_path = Self::find_executable(submission_dir, definition)?;
-        let is_debug = env.get("STACKCLASS_DEBUG").is_some_and(|v| v == "true");
-        let timeout = env
-            .get("STACKCLASS_TIMEOUT_SECONDS")
-            .and_then(|v| v.parse().ok())
-            .map(Duration::from_secs)
-            .unwrap_or(Duration::from_secs(15));
+        let executable_path = Self::find_executable(&repository_dir, definition)?;
+        let is_debug = env.get("STACKCLASS_DEBUG").is_some_and(|v| parse_bool_env(v));
+        let timeout = match env.get("STACKCLASS_TIMEOUT_SECONDS") {
+            Some(value) => parse_seconds("STACKCLASS_TIMEOUT_SECONDS", value)?,
+            None => Duration::from_secs(15),
+        };
+        let total_timeout = env
+            .get("STACKCLASS_TOTAL_TIMEOUT_SECONDS")
+            .map(|value| parse_seconds("STACKCLASS_TOTAL_TIMEOUT_SECONDS", value))
+            .transpose()?;
 
         let should_skip_anti_cheat =
-            env.get("STACKCLASS_SKIP_ANTI_CHEAT").is_some_and(|v| v == "true");
+            env.get("STACKCLASS_SKIP_ANTI_CHEAT").is_some_and(|v| parse_bool_env(v));
+
+        let output_format = OutputFormat::from_env(&env);
 
-        Ok(Self { executable_path, is_debug, cases, timeout, env, should_skip_anti_cheat })
+        let show_timing = env.get("STACKCLASS_TIMING").is_some_and(|v| parse_bool_env(v));
+        let list_only = env.get("STACKCLASS_LIST_ONLY").is_some_and(|v| parse_bool_env(v));
+        let strict_definition =
+            env.get("STACKCLASS_STRICT_DEFINITION").is_some_and(|v| parse_bool_env(v));
+        let ignore_unknown_cases =
+            env.get("STACKCLASS_IGNORE_UNKNOWN_CASES").is_some_and(|v| parse_bool_env(v));
+        let sort_stages = env.get("STACKCLASS_SORT_STAGES").is_some_and(|v| parse_bool_env(v));
+        let only_tags = env
+            .get("STACKCLASS_ONLY_TAGS")
+            .map(|tags| {
+                tags.split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let only_slug = env
+            .get("STACKCLASS_ONLY_SLUG")
+            .map(|slug| slug.trim().to_string())
+            .filter(|slug| !slug.is_empty());
+
+        Ok(Self {
+            repository_dir,
+            executable_path,
+            is_debug,
+            cases,
+            timeout,
+            total_timeout,
+            env,
+            should_skip_anti_cheat,
+            output_format,
+            show_timing,
+            list_only,
+            strict_definition,
+            ignore_unknown_cases,
+            sort_stages,
+            only_tags,
+            only_slug,
+        })
     }
 
-    /// Locates the executable in the submission directory based on the `Definition`.
-    fn find_executable(dir: &str, definition: &Definition) -> Result<PathBuf> {
-        let executable_path = PathBuf::from(dir).join(&definition.executable_name);
+    /// Loads the test cases from `STACKCLASS_TEST_CASES_JSON` or from the file named by
+    /// `STACKCLASS_TEST_CASES_FILE`. Both may be set as long as they describe the same cases.
+    fn load_cases(env: &HashMap<String, String>) -> Result<Vec<ContextCase>> {
+        // serde_json's message already ends with the line and column of the problem.
+        let parse = |source: &str, json: &str| -> Result<Vec<ContextCase>> {
+            serde_json::from_str(json).map_err(|e| {
+                let reason = match json_kind(json) {
+                    Some(kind) => format!("expected a JSON array of test cases but found {kind}"),
+                    None => e.to_string(),
+                };
+                TesterError::JsonParse(format!("failed to parse {source}: {reason}"))
+            })
+        };
+
+        let inline_cases = env
+            .get("STACKCLASS_TEST_CASES_JSON")
+            .map(|json| parse("STACKCLASS_TEST_CASES_JSON", json))
+            .transpose()?;
+
+        let file_cases = env
+            .get("STACKCLASS_TEST_CASES_FILE")
+            .map(|path| {
+                let json = fs::read_to_string(path).map_err(|e| {
+                    TesterError::io_with_context(
+                        e,
+                        &format!("failed to read test cases file {path}"),
+                    )
+                })?;
+                parse(&format!("test cases file {path}"), &json)
+            })
+            .transpose()?;
 
-        if executable_path.exists() {
-            debug!("executable path: {}", executable_path.display());
-            return Ok(executable_path);
+        match (inline_cases, file_cases) {
+            (Some(inline_cases), Some(file_cases)) if inline_cases != file_cases => {
+                Err(TesterError::InvalidTestCase(
+                    "STACKCLASS_TEST_CASES_JSON and STACKCLASS_TEST_CASES_FILE describe different \
+                     test cases"
+                        .into(),
+                ))
+            }
+            (_, Some(cases)) | (Some(cases), None) => Ok(cases),
+            (None, None) => Err(TesterError::MissingEnvVar("STACKCLASS_TEST_CASES_JSON".into())),
         }
+    }
+
+    /// Locates the executable in the submission directory based on the `Definition`.
+    ///
+    /// The executable names must resolve to paths inside the submission directory. Falling back
+    /// to the legacy name logs a warning asking the author to rename their file.
+    fn find_executable(dir: &Path, definition: &Definition) -> Result<PathBuf> {
+        let file_names =
+            std::iter::once(&definition.executable_name).chain(&definition.legacy_executable_name);
 
-        if let Some(legacy_file_name) = &definition.legacy_executable_name {
-            let legacy_executable_path = PathBuf::from(dir).join(legacy_file_name);
-            if legacy_executable_path.exists() {
-                return Ok(legacy_executable_path);
+        // The executable may be a symlink to a program built elsewhere, so only the name itself
+        // has to stay inside the repository.
+        let mut rejected = None;
+        for (index, file_name) in file_names.enumerate() {
+            match join_within(dir, file_name) {
+                Ok(executable_path) if is_executable(&executable_path) => {
+                    if index > 0 {
+                        warn!(
+                            "{file_name} is deprecated, please rename it to {}",
+                            definition.executable_name
+                        );
+                    }
+                    debug!("executable path: {}", executable_path.display());
+                    return Ok(executable_path);
+                }
+                Err(err @ TesterError::InvalidPath(_)) => {
+                    rejected.get_or_insert(err);
+                }
+                _ => {}
             }
         }
 
-        Err(TesterError::ExecutableNotFound(executable_path))
+        Err(rejected.unwrap_or_else(|| {
+            TesterError::ExecutableNotFound(dir.join(&definition.executable_name))
+        }))
+    }
+}
+
+/// Describes the top-level value of `json` if it is valid JSON but not an array, such as a single
+/// test case sent without the surrounding brackets.
+fn json_kind(json: &str) -> Option<&'static str> {
+    match serde_json::from_str(json).ok()? {
+        serde_json::Value::Array(_) => None,
+        serde_json::Value::Object(_) => Some("an object"),
+        serde_json::Value::String(_) => Some("a string"),
+        serde_json::Value::Number(_) => Some("a number"),
+        serde_json::Value::Bool(_) => Some("a boolean"),
+        serde_json::Value::Null => Some("null"),
     }
 }
+
+/// Parses the value of the variable `key` as a positive whole number of seconds.
+fn parse_seconds(key: &str, value: &str) -> Result<Duration> {
+    match value.trim().parse::<u64>() {
+        Ok(0) => Err(TesterError::InvalidConfig(format!("{key} must be greater than zero"))),
+        Ok(seconds) => Ok(Duration::from_secs(seconds)),
+        Err(_) => Err(TesterError::InvalidConfig(format!(
+            "{key} must be a whole number of seconds, got {value:?}"
+        ))),
+    }
+}
+
+/// Interprets a boolean flag from the environment, accepting `true`, `1`, `yes` and `on` in any
+/// letter case.
+pub(crate) fn parse_bool_env(value: &str) -> bool {
+    ["true", "1", "yes", "on"].iter().any(|accepted| value.trim().eq_ignore_ascii_case(accepted))
+}
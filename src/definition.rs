@@ -12,7 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::case::Case;
+use crate::{Result, TesterError, case::Case};
+use std::collections::HashSet;
 
 /// Represents a test definition, containing metadata and test cases.
 #[derive(Debug, Default)]
@@ -28,11 +29,138 @@ pub struct Definition {
 
     /// A collection of anti-cheat test cases for additional validation.
     pub anti_cheat_cases: Vec<Case>,
+
+    /// A command (program followed by its arguments) that builds the submission, run once in the
+    /// repository directory before any stage.
+    pub build_command: Option<Vec<String>>,
 }
 
 impl Definition {
+    /// Creates a builder for assembling a `Definition` fluently.
+    pub fn builder() -> DefinitionBuilder {
+        DefinitionBuilder::default()
+    }
+
     /// Finds a test case by its slug.
     pub fn find_case(&self, slug: &str) -> Option<&Case> {
         self.cases.iter().find(|case| case.slug == slug)
     }
+
+    /// Finds an anti-cheat test case by its slug.
+    pub fn find_anti_cheat_case(&self, slug: &str) -> Option<&Case> {
+        self.anti_cheat_cases.iter().find(|case| case.slug == slug)
+    }
+
+    /// Appends a test case, e.g. one registered by a separate module of the tester. Fails if a
+    /// case, regular or anti-cheat, already has its slug.
+    pub fn add_case(&mut self, case: Case) -> Result<()> {
+        self.check_new_slug(&case.slug)?;
+        self.cases.push(case);
+        Ok(())
+    }
+
+    /// Appends an anti-cheat test case. Fails if a case, regular or anti-cheat, already has its
+    /// slug.
+    pub fn add_anti_cheat_case(&mut self, case: Case) -> Result<()> {
+        self.check_new_slug(&case.slug)?;
+        self.anti_cheat_cases.push(case);
+        Ok(())
+    }
+
+    /// Ensures `slug` is not already taken by a regular or anti-cheat case.
+    fn check_new_slug(&self, slug: &str) -> Result<()> {
+        if self.find_case(slug).is_some() || self.find_anti_cheat_case(slug).is_some() {
+            return Err(TesterError::InvalidTestCase(format!("duplicate test case slug: {slug}")));
+        }
+
+        Ok(())
+    }
+
+    /// Ensures no two cases, regular or anti-cheat, share the same slug.
+    pub fn check_unique_slugs(&self) -> Result<()> {
+        let mut seen = HashSet::new();
+
+        for case in self.cases.iter().chain(&self.anti_cheat_cases) {
+            if !seen.insert(case.slug.as_str()) {
+                return Err(TesterError::InvalidTestCase(format!(
+                    "duplicate test case slug: {}",
+                    case.slug
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the structure of the definition: the executable name and every slug must be
+    /// non-empty, and no two cases, regular or anti-cheat, may share a slug.
+    ///
+    /// This looks at the definition alone. [`Tester::validate`](crate::Tester::validate) calls it
+    /// and then checks the definition against the test cases the platform expects, so that a
+    /// missing anti-cheat case is reported rather than skipped.
+    pub fn validate(&self) -> Result<()> {
+        if self.executable_name.is_empty() {
+            return Err(TesterError::InvalidDefinition("executable name cannot be empty".into()));
+        }
+        if self.cases.iter().chain(&self.anti_cheat_cases).any(|case| case.slug.is_empty()) {
+            return Err(TesterError::InvalidTestCase("test case slug cannot be empty".into()));
+        }
+
+        self.check_unique_slugs()
+    }
+}
+
+/// Builder for [`Definition`], validating its invariants on [`DefinitionBuilder::build`].
+#[derive(Debug, Default)]
+pub struct DefinitionBuilder {
+    definition: Definition,
+}
+
+impl DefinitionBuilder {
+    /// Sets the name of the executable associated with the test.
+    pub fn executable_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.definition.executable_name = name.into();
+        self
+    }
+
+    /// Sets the legacy name of the executable.
+    pub fn legacy_executable_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.definition.legacy_executable_name = Some(name.into());
+        self
+    }
+
+    /// Sets the command that builds the submission before any stage runs.
+    pub fn build_command<I, S>(mut self, command: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.definition.build_command = Some(command.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Adds a test case.
+    pub fn case(mut self, case: Case) -> Self {
+        self.definition.cases.push(case);
+        self
+    }
+
+    /// Adds multiple test cases.
+    pub fn cases<I: IntoIterator<Item = Case>>(mut self, cases: I) -> Self {
+        self.definition.cases.extend(cases);
+        self
+    }
+
+    /// Adds an anti-cheat test case.
+    pub fn anti_cheat_case(mut self, case: Case) -> Self {
+        self.definition.anti_cheat_cases.push(case);
+        self
+    }
+
+    /// Builds the `Definition`, ensuring it passes [`Definition::validate`].
+    pub fn build(self) -> Result<Definition> {
+        self.definition.validate()?;
+
+        Ok(self.definition)
+    }
 }
@@ -12,9 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{io, path::PathBuf, time::Duration};
+use std::{io, path::PathBuf, process::ExitStatus, time::Duration};
 use thiserror::Error;
 
+use crate::executable::{STDERR_TAIL_LINES, tail_lines};
+
 pub type Result<T, E = TesterError> = std::result::Result<T, E>;
 
 #[derive(Debug, Error)]
@@ -40,9 +42,24 @@ pub enum TesterError {
     #[error("Invalid test case: {0}")]
     InvalidTestCase(String),
 
+    #[error("Invalid test definition: {0}")]
+    InvalidDefinition(String),
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
     #[error("Process execution failed: {0}")]
     ProcessExecution(String),
 
+    #[error("{0} is not executable (check its permissions)")]
+    ExecutableNotExecutable(PathBuf),
+
+    #[error("{0} is not in a format this system can execute (was it built for another platform?)")]
+    ExecFormatError(PathBuf),
+
+    #[error("The interpreter for {0} was not found (check its shebang line)")]
+    InterpreterNotFound(PathBuf),
+
     #[error("Invalid path: {0}")]
     InvalidPath(String),
 
@@ -75,6 +92,57 @@ pub enum TesterError {
 
     #[error("Process wait timed out after {0:?}")]
     WaitTimeout(Duration),
+
+    #[error(
+        "Process wait timed out after {timeout:?}{}",
+        stderr_suffix(tail_lines(stderr, STDERR_TAIL_LINES))
+    )]
+    WaitTimeoutWithOutput { timeout: Duration, stdout: Vec<u8>, stderr: Vec<u8> },
+
+    #[error("Process wait timed out after {timeout:?}, and killing the process failed: {reason}")]
+    WaitTimeoutKillFailed { timeout: Duration, reason: String },
+
+    #[error("Process failed ({status}){}", stderr_suffix(stderr_tail.as_bytes()))]
+    ProcessFailed { status: ExitStatus, stderr_tail: String },
+
+    #[error(
+        "Build command failed ({status}){}{}",
+        stream_suffix("stdout", stdout.as_bytes()),
+        stderr_suffix(stderr.as_bytes())
+    )]
+    BuildFailed { status: ExitStatus, stdout: String, stderr: String },
+
+    #[error(
+        "Build command timed out after {timeout:?}{}{}",
+        stream_suffix("stdout", stdout.as_bytes()),
+        stderr_suffix(stderr.as_bytes())
+    )]
+    BuildTimedOut { timeout: Duration, stdout: String, stderr: String },
+
+    #[error("Process output closed before the expected data arrived")]
+    OutputClosed,
+
+    #[error("No response received within {0:?}")]
+    ResponseTimeout(Duration),
+
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+
+    #[error("Resource limit exceeded: {0}")]
+    ResourceLimitExceeded(String),
+}
+
+/// Formats the tail of a process's stderr for appending to an error message.
+fn stderr_suffix(tail: &[u8]) -> String {
+    stream_suffix("stderr", tail)
+}
+
+/// Formats output of a process from the stream named `name` for appending to an error message.
+fn stream_suffix(name: &str, output: &[u8]) -> String {
+    if output.is_empty() {
+        return String::new();
+    }
+    format!("\n{name}:\n{}", String::from_utf8_lossy(output).trim_end())
 }
 
 impl TesterError {
@@ -90,7 +158,13 @@ impl TesterError {
 
     /// Checks if the error is a timeout error.
     pub fn is_timeout(&self) -> bool {
-        matches!(self, Self::Timeout(_) | Self::WaitTimeout(_))
+        matches!(
+            self,
+            Self::Timeout(_) |
+                Self::WaitTimeout(_) |
+                Self::WaitTimeoutWithOutput { .. } |
+                Self::WaitTimeoutKillFailed { .. }
+        )
     }
 
     /// Determines whether the error is recoverable.
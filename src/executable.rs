@@ -12,16 +12,65 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{Result, TesterError};
+use crate::{Result, StdinWriter, StdoutReader, TesterError};
 use std::{
-    io::Read,
-    path::PathBuf,
+    collections::HashMap,
+    ffi::OsStr,
+    fs::File,
+    io::{ErrorKind, Read, Write},
+    path::{Path, PathBuf},
     process::{Child, Command, ExitStatus, Stdio},
-    sync::{Arc, Mutex, mpsc},
-    thread,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError},
+    },
+    thread::{self, JoinHandle},
     time::{Duration, Instant},
 };
 
+/// Maximum size of a chunk forwarded by a capture thread.
+const CAPTURE_CHUNK_SIZE: usize = 8192;
+
+/// How long `wait` keeps collecting output after the process has exited, for output still on its
+/// way from the capture threads. Only reached if a process the child spawned keeps the pipes open.
+const OUTPUT_DRAIN_GRACE: Duration = Duration::from_secs(1);
+
+/// Number of trailing stderr lines reported by [`Executable::last_stderr`] and in errors.
+pub(crate) const STDERR_TAIL_LINES: usize = 10;
+
+/// Default number of bytes of output that may be in flight between the capture threads and the
+/// `Executable`.
+const DEFAULT_CAPTURE_BUFFER: usize = 1024 * CAPTURE_CHUNK_SIZE;
+
+/// Describes how a waited-on process came to an end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    /// The process exited normally with the given exit code.
+    Exited(i32),
+
+    /// The process was terminated by the given signal (Unix only).
+    Signaled(i32),
+
+    /// The process did not exit before the timeout and was killed.
+    TimedOut,
+}
+
+impl From<ExitStatus> for ExitReason {
+    fn from(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+
+            if let Some(signal) = status.signal() {
+                return Self::Signaled(signal);
+            }
+        }
+
+        Self::Exited(status.code().unwrap_or(-1))
+    }
+}
+
 /// Represents an executable process with configurable execution parameters.
 ///
 /// This struct provides methods to start, manage, and interact with a child process,
@@ -34,20 +83,151 @@ pub struct Executable {
     /// Maximum duration the process is allowed to run before timing out.
     timeout: Duration,
 
+    /// Interval between process status checks while waiting for the process to exit.
+    poll_interval: Duration,
+
     /// Optional working directory for the process.
     working_dir: Option<PathBuf>,
 
+    /// Additional environment variables passed to the process.
+    env: HashMap<String, String>,
+
+    /// Whether the process starts from an empty environment instead of inheriting the tester's.
+    clear_env: bool,
+
+    /// Maximum size of the process's virtual memory in bytes (Unix only).
+    memory_limit: Option<u64>,
+
+    /// Maximum CPU time the process may consume (Unix only).
+    cpu_time_limit: Option<Duration>,
+
     /// Handle to the child process, wrapped in an `Arc<Mutex>` for thread safety.
-    process: Option<Arc<Mutex<Child>>>,
+    process: Option<Arc<Mutex<ChildProcess>>>,
+
+    /// Stdout captured from the process so far.
+    stdout: Vec<u8>,
+
+    /// Stderr captured from the process so far.
+    stderr: Vec<u8>,
+
+    /// Offset into `stdout` up to which bytes have been handed out by incremental reads.
+    stdout_read_pos: usize,
+
+    /// Number of bytes of output the capture threads may queue before they drop further output.
+    capture_buffer: usize,
+
+    /// State shared with the capture threads of the most recently started process.
+    capture: Arc<CaptureState>,
 
-    /// Captured stdout of the process, if available.
-    stdout: Option<Vec<u8>>,
+    /// Maximum number of bytes kept of each output stream, beyond which the process is killed.
+    max_output_bytes: Option<usize>,
 
-    /// Captured stderr of the process, if available.
-    stderr: Option<Vec<u8>>,
+    /// Whether the process's stdout is captured; otherwise it is discarded.
+    capture_stdout: bool,
+
+    /// Whether the process's stderr is captured; otherwise it is discarded.
+    capture_stderr: bool,
+
+    /// Whether to keep a merged stdout/stderr stream ordered by arrival.
+    combine_output: bool,
+
+    /// Whether to forward the process's output to the tester's own stdout/stderr as it arrives.
+    tee: bool,
+
+    /// Buffer that forwarded output goes to instead of the tester's stdout/stderr, if set.
+    tee_sink: Option<OutputSink>,
+
+    /// How long a process still running on drop gets to exit after `SIGTERM` before it is killed.
+    drop_grace: Duration,
+
+    /// Merged stdout/stderr chunks as `(sequence, is_stdout, data)`, when enabled.
+    combined: Vec<(u64, bool, Vec<u8>)>,
 
     /// Receiver for capturing stdout and stderr asynchronously.
-    rx: Option<mpsc::Receiver<(Vec<u8>, bool)>>,
+    rx: Option<mpsc::Receiver<Chunk>>,
+
+    /// Capture threads of the most recently started process.
+    capture_threads: Vec<JoinHandle<()>>,
+
+    /// Peak resident set size in bytes of the last process reaped by `wait` (Unix only).
+    last_max_rss: Option<u64>,
+
+    /// Exit status of the last process reaped by `wait`.
+    last_status: Option<ExitStatus>,
+
+    /// Registry every started process is recorded in, so that its owner can kill it later.
+    children: Option<ChildRegistry>,
+}
+
+/// Processes started by executables that share the registry, killed together on cleanup.
+pub(crate) type ChildRegistry = Arc<Mutex<Vec<Arc<Mutex<ChildProcess>>>>>;
+
+/// A child process that is reaped only through its own methods, which remember how it exited.
+///
+/// On Unix the process is reaped with `wait4` to learn the resources it used, behind the back of
+/// [`Child`]. Keeping the exit here means the process is never waited for or signaled again once
+/// reaped, when its pid may already belong to another process.
+#[derive(Debug)]
+pub(crate) struct ChildProcess {
+    /// The spawned process.
+    child: Child,
+
+    /// Exit status and resource usage, once the process has been reaped.
+    exit: Option<(ExitStatus, Option<ResourceUsage>)>,
+}
+
+/// A shared buffer that collects output to be written out later in one piece.
+pub(crate) type OutputSink = Arc<Mutex<Vec<u8>>>;
+
+/// Where a capture thread forwards the output it reads.
+enum Tee {
+    /// Output is only captured.
+    Off,
+    /// Output is also written to the matching stream of the current process.
+    Console,
+    /// Output is also appended to a shared buffer.
+    Buffer(OutputSink),
+}
+
+/// State shared between an `Executable` and the capture threads of its process.
+#[derive(Debug, Default)]
+struct CaptureState {
+    /// Maximum number of bytes that may be in flight.
+    capacity: usize,
+
+    /// Bytes sent by the capture threads that have not been collected yet.
+    in_flight: AtomicUsize,
+
+    /// Maximum number of bytes kept of each stream.
+    max_output_bytes: Option<usize>,
+
+    /// Whether output was dropped because the capture buffer was full.
+    dropped: AtomicBool,
+
+    /// Whether a stream exceeded `max_output_bytes`.
+    truncated: AtomicBool,
+}
+
+/// Resources used by a process, as reported by the OS when it was reaped.
+#[derive(Debug, Clone, Copy)]
+struct ResourceUsage {
+    /// Peak resident set size in bytes.
+    max_rss: u64,
+
+    /// User and system CPU time consumed.
+    cpu_time: Duration,
+}
+
+/// A chunk of output read by a capture thread.
+struct Chunk {
+    /// Monotonic sequence number shared by the stdout and stderr capture threads.
+    seq: u64,
+
+    /// The bytes read.
+    data: Vec<u8>,
+
+    /// Whether the bytes came from stdout (`true`) or stderr (`false`).
+    is_stdout: bool,
 }
 
 /// Creates a shallow clone of the `Executable`.
@@ -56,54 +236,250 @@ impl Clone for Executable {
         Self {
             path: self.path.clone(),
             timeout: self.timeout,
+            poll_interval: self.poll_interval,
             working_dir: self.working_dir.clone(),
+            env: self.env.clone(),
+            clear_env: self.clear_env,
+            memory_limit: self.memory_limit,
+            cpu_time_limit: self.cpu_time_limit,
             process: self.process.clone(),
             stdout: self.stdout.clone(),
             stderr: self.stderr.clone(),
+            stdout_read_pos: self.stdout_read_pos,
+            capture_buffer: self.capture_buffer,
+            capture: self.capture.clone(),
+            max_output_bytes: self.max_output_bytes,
+            capture_stdout: self.capture_stdout,
+            capture_stderr: self.capture_stderr,
+            combine_output: self.combine_output,
+            tee: self.tee,
+            tee_sink: self.tee_sink.clone(),
+            drop_grace: self.drop_grace,
+            combined: self.combined.clone(),
             rx: None,
+            capture_threads: Vec::new(),
+            last_max_rss: self.last_max_rss,
+            last_status: self.last_status,
+            children: self.children.clone(),
         }
     }
 }
 
-/// Ensures the process is killed when the `Executable` is dropped.
+/// Ensures the process is killed when the `Executable` is dropped, after the grace period set
+/// with [`Executable::with_drop_grace`]. Like [`Executable::kill`], this collects the output
+/// still in flight, so a tee'd or combined stream is complete.
 impl Drop for Executable {
     fn drop(&mut self) {
-        if self.is_running() {
-            let _ = self.kill(); // Ignore errors during drop
+        if !self.is_running() {
+            return;
+        }
+
+        // Ignore errors during drop
+        if self.drop_grace.is_zero() {
+            let _ = self.kill();
+        } else {
+            let _ = self.shutdown(self.drop_grace);
+            self.drain_until_closed();
         }
     }
 }
 
 impl Executable {
     /// Creates a new `Executable` instance.
+    ///
+    /// On Windows, an existing file that is not executable is rejected with
+    /// [`TesterError::ProcessExecution`] rather than [`TesterError::ExecutableNotFound`].
     pub fn new(path: PathBuf) -> Result<Self> {
-        if !path.exists() {
+        if !is_executable(&path) {
+            #[cfg(windows)]
+            if path.is_file() {
+                return Err(TesterError::ProcessExecution(format!(
+                    "{} is not an executable file",
+                    path.display()
+                )));
+            }
+
             return Err(TesterError::ExecutableNotFound(path));
         }
 
         Ok(Self {
             path,
             timeout: Duration::from_secs(10),
+            poll_interval: Duration::from_millis(100),
             working_dir: None,
+            env: HashMap::new(),
+            clear_env: false,
+            memory_limit: None,
+            cpu_time_limit: None,
             process: None,
-            stdout: None,
-            stderr: None,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+            stdout_read_pos: 0,
+            capture_buffer: DEFAULT_CAPTURE_BUFFER,
+            capture: Arc::default(),
+            max_output_bytes: None,
+            capture_stdout: true,
+            capture_stderr: true,
+            combine_output: false,
+            tee: false,
+            tee_sink: None,
+            drop_grace: Duration::ZERO,
+            combined: Vec::new(),
             rx: None,
+            capture_threads: Vec::new(),
+            last_max_rss: None,
+            last_status: None,
+            children: None,
         })
     }
 
+    /// Returns an executable with the same configuration but no process or captured output,
+    /// which records every process it starts in `children`.
+    pub(crate) fn tracked_copy(&self, children: ChildRegistry) -> Self {
+        Self {
+            path: self.path.clone(),
+            timeout: self.timeout,
+            poll_interval: self.poll_interval,
+            working_dir: self.working_dir.clone(),
+            env: self.env.clone(),
+            clear_env: self.clear_env,
+            memory_limit: self.memory_limit,
+            cpu_time_limit: self.cpu_time_limit,
+            process: None,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+            stdout_read_pos: 0,
+            capture_buffer: self.capture_buffer,
+            capture: Arc::default(),
+            max_output_bytes: self.max_output_bytes,
+            capture_stdout: self.capture_stdout,
+            capture_stderr: self.capture_stderr,
+            combine_output: self.combine_output,
+            tee: self.tee,
+            tee_sink: self.tee_sink.clone(),
+            drop_grace: self.drop_grace,
+            combined: Vec::new(),
+            rx: None,
+            capture_threads: Vec::new(),
+            last_max_rss: None,
+            last_status: None,
+            children: Some(children),
+        }
+    }
+
     /// Sets a custom timeout for the process.
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
 
+    /// Sets the interval between process status checks while waiting, defaulting to 100ms.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
     /// Sets the working directory for the process.
     pub fn with_working_dir(mut self, dir: PathBuf) -> Self {
         self.working_dir = Some(dir);
         self
     }
 
+    /// Sets the environment variables passed to the process, replacing any set previously.
+    pub fn with_env(mut self, vars: HashMap<String, String>) -> Self {
+        self.env = vars;
+        self
+    }
+
+    /// Sets whether the process starts from an empty environment, so that only the variables
+    /// configured on this `Executable` are visible to it.
+    pub fn with_clear_env(mut self, clear: bool) -> Self {
+        self.clear_env = clear;
+        self
+    }
+
+    /// Adds or overrides a single environment variable passed to the process.
+    pub fn env<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) -> &mut Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Limits the virtual memory of the process to `bytes` (Unix only).
+    pub fn with_memory_limit(mut self, bytes: u64) -> Self {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    /// Limits the CPU time the process may consume, rounded down to whole seconds with a minimum
+    /// of one second (Unix only).
+    pub fn with_cpu_time_limit(mut self, limit: Duration) -> Self {
+        self.cpu_time_limit = Some(limit);
+        self
+    }
+
+    /// Limits how many bytes of output the capture threads may queue until it is collected by
+    /// `wait`, `read_until` or an incremental read (default: 8 MiB).
+    ///
+    /// The capture threads always keep reading, so a process is never blocked on a full pipe.
+    /// Output that arrives while the buffer is full is dropped, which
+    /// [`Executable::output_truncated`] reports.
+    pub fn with_capture_buffer(mut self, bytes: usize) -> Self {
+        self.capture_buffer = bytes.max(1);
+        self
+    }
+
+    /// Keeps at most `bytes` of each output stream of the process, so that a program printing
+    /// without end cannot exhaust the tester's memory.
+    ///
+    /// Once a stream exceeds the limit, the rest of its output is read but neither kept nor
+    /// forwarded by [`Executable::with_tee`], and [`Executable::output_truncated`] reports it.
+    /// `wait` and the `run` methods then kill the process and return the output kept so far
+    /// along with the status of the killed process.
+    pub fn with_max_output_bytes(mut self, bytes: usize) -> Self {
+        self.max_output_bytes = Some(bytes);
+        self
+    }
+
+    /// Sets which of the process's output streams are captured (both by default).
+    ///
+    /// A stream that is not captured is discarded, saving the memory and CPU spent on output no
+    /// assertion looks at. `wait` and the `run` methods return it as empty.
+    pub fn with_capture(mut self, stdout: bool, stderr: bool) -> Self {
+        self.capture_stdout = stdout;
+        self.capture_stderr = stderr;
+        self
+    }
+
+    /// Keeps a merged stdout/stderr stream that preserves the order in which chunks arrived.
+    pub fn with_combined_output(mut self, enabled: bool) -> Self {
+        self.combine_output = enabled;
+        self
+    }
+
+    /// Forwards the output of the process to the tester's own stdout and stderr in real time, in
+    /// addition to capturing it.
+    pub fn with_tee(mut self, enabled: bool) -> Self {
+        self.tee = enabled;
+        self
+    }
+
+    /// Gives a process that is still running when the `Executable` is dropped up to `grace` to
+    /// exit after `SIGTERM`, e.g. to close its listening sockets, before it is killed. Defaults to
+    /// zero, which kills it right away.
+    ///
+    /// See [`Executable::shutdown`] for how this behaves on other platforms.
+    pub fn with_drop_grace(mut self, grace: Duration) -> Self {
+        self.drop_grace = grace;
+        self
+    }
+
+    /// Sends the output forwarded by [`Executable::with_tee`] to `sink` instead of the tester's
+    /// stdout and stderr.
+    pub(crate) fn with_tee_sink(mut self, sink: OutputSink) -> Self {
+        self.tee_sink = Some(sink);
+        self
+    }
+
     /// Checks if the process is currently running.
     pub fn is_running(&self) -> bool {
         if let Some(process) = &self.process {
@@ -118,126 +494,738 @@ impl Executable {
         }
     }
 
+    /// Returns the OS-assigned process id of the child, if it is currently running.
+    pub fn pid(&self) -> Option<u32> {
+        let process = self.process.as_ref()?;
+        let mut process = process.lock().unwrap();
+        match process.try_wait() {
+            Ok(None) => Some(process.id()),
+            _ => None,
+        }
+    }
+
     /// Runs the executable with the given arguments and returns its output.
     /// This is a blocking call suitable for short-lived processes.
     pub fn run(&mut self, args: &[&str]) -> Result<(Vec<u8>, Vec<u8>, ExitStatus)> {
-        if self.is_running() {
-            return Err(TesterError::ProcessAlreadyRunning);
-        }
+        self.run_args(args, Stdio::null())
+    }
 
-        let mut cmd = Command::new(&self.path);
-        cmd.args(args).stdin(Stdio::null());
+    /// Like [`Executable::run`], but takes owned arguments, such as ones built at runtime.
+    pub fn run_owned(&mut self, args: &[String]) -> Result<(Vec<u8>, Vec<u8>, ExitStatus)> {
+        self.run_args(args, Stdio::null())
+    }
 
-        if let Some(dir) = &self.working_dir {
-            cmd.current_dir(dir);
+    /// Like [`Executable::run`], but feeds `input` to the process's stdin and then closes it.
+    ///
+    /// The whole run, including writing the input, is bounded by the timeout, after which the
+    /// process is killed. If the input could not be written in time, because the process stopped
+    /// reading it, [`TesterError::WaitTimeout`] is returned. Otherwise the timeout is reported
+    /// like [`Executable::wait`] does, with the output captured so far.
+    pub fn run_with_stdin(
+        &mut self,
+        input: &[u8],
+        args: &[&str],
+    ) -> Result<(Vec<u8>, Vec<u8>, ExitStatus)> {
+        self.start_args(args, Stdio::piped())?;
+
+        let process = self.process.as_ref().ok_or(TesterError::NoProcessRunning)?;
+        let mut stdin =
+            process.lock().unwrap().child.stdin.take().ok_or(TesterError::StdinCaptureFailed)?;
+
+        // Write from a separate thread so a process that produces output before consuming all of
+        // its input cannot deadlock against us. Killing a process that stops reading its input
+        // closes the pipe, which unblocks the writer.
+        let input = input.to_vec();
+        let written = Arc::new(AtomicBool::new(false));
+        let writer_written = written.clone();
+        thread::spawn(move || {
+            // The process may exit without reading everything, which is not an error here.
+            if stdin.write_all(&input).is_ok() {
+                writer_written.store(true, Ordering::SeqCst);
+            }
+        });
+
+        match self.wait() {
+            Err(TesterError::WaitTimeoutWithOutput { timeout, .. })
+                if !written.load(Ordering::SeqCst) =>
+            {
+                Err(TesterError::WaitTimeout(timeout))
+            }
+            result => result,
         }
+    }
 
-        let output = cmd.output().map_err(|e| TesterError::ProcessExecution(e.to_string()))?;
+    /// Like [`Executable::run_with_stdin`], but for text protocols: sends `stdin` and returns
+    /// stdout and stderr as strings.
+    ///
+    /// Invalid UTF-8 in the output is replaced with `U+FFFD`, so tests that check binary output
+    /// should use [`Executable::run_with_stdin`] instead.
+    pub fn run_text(&mut self, stdin: &str, args: &[&str]) -> Result<(String, String, ExitStatus)> {
+        let (stdout, stderr, status) = self.run_with_stdin(stdin.as_bytes(), args)?;
+        Ok((
+            String::from_utf8_lossy(&stdout).into_owned(),
+            String::from_utf8_lossy(&stderr).into_owned(),
+            status,
+        ))
+    }
+
+    /// Like [`Executable::run`], but streams the file at `path` to the process's stdin without
+    /// loading it into memory.
+    pub fn run_with_stdin_file(
+        &mut self,
+        path: &Path,
+        args: &[&str],
+    ) -> Result<(Vec<u8>, Vec<u8>, ExitStatus)> {
+        let file = File::open(path).map_err(|err| {
+            TesterError::io_with_context(err, &format!("failed to open {}", path.display()))
+        })?;
+
+        self.run_args(args, Stdio::from(file))
+    }
 
-        Ok((output.stdout, output.stderr, output.status))
+    fn run_args<S: AsRef<OsStr>>(
+        &mut self,
+        args: &[S],
+        stdin: Stdio,
+    ) -> Result<(Vec<u8>, Vec<u8>, ExitStatus)> {
+        self.start_args(args, stdin)?;
+        self.wait()
     }
 
     /// Starts the process with the given arguments.
     /// This is suitable for long-lived processes.
+    ///
+    /// The process's stdin is kept open so input can be fed with [`Executable::write_line`];
+    /// call [`Executable::close_stdin`] to signal EOF.
     pub fn start(&mut self, args: &[&str]) -> Result<()> {
+        self.start_args(args, Stdio::piped())
+    }
+
+    /// Kills the running process, if any, and starts the program again with `args`, keeping the
+    /// configured timeout, working directory and environment. Output captured from the previous
+    /// process is discarded.
+    ///
+    /// This suits stages that check a program recovers its persisted state after a crash.
+    pub fn restart(&mut self, args: &[&str]) -> Result<()> {
+        self.kill()?;
+        self.start_args(args, Stdio::piped())
+    }
+
+    /// Like [`Executable::start`], but takes owned arguments, such as ones built at runtime.
+    pub fn start_owned(&mut self, args: &[String]) -> Result<()> {
+        self.start_args(args, Stdio::piped())
+    }
+
+    fn start_args<S: AsRef<OsStr>>(&mut self, args: &[S], stdin: Stdio) -> Result<()> {
         if self.is_running() {
             return Err(TesterError::ProcessAlreadyRunning);
         }
 
+        self.reap_capture_threads();
+
         let mut cmd = Command::new(&self.path);
-        cmd.args(args).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        cmd.args(args).stdin(stdin).stdout(Stdio::piped()).stderr(Stdio::piped());
 
-        if let Some(dir) = &self.working_dir {
-            cmd.current_dir(dir);
-        }
+        self.configure(&mut cmd);
 
-        let mut process = cmd.spawn().map_err(|e| TesterError::ProcessExecution(e.to_string()))?;
+        // Run the child in its own process group so that killing it also takes down any
+        // processes it spawned.
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
 
-        // Use a bounded channel to avoid unbounded memory usage
-        let (tx, rx) = mpsc::sync_channel(1024);
-        let mut stdout = process.stdout.take().ok_or(TesterError::StdoutCaptureFailed)?;
-        let mut stderr = process.stderr.take().ok_or(TesterError::StderrCaptureFailed)?;
+        let mut process = cmd.spawn().map_err(|e| spawn_error(&self.path, e))?;
 
-        // Spawn a thread to capture stdout asynchronously.
-        let tx1 = tx.clone();
-        thread::spawn(move || {
-            let mut stdout_buf = Vec::new();
-            match stdout.read_to_end(&mut stdout_buf) {
-                Err(_) => {
-                    let _ = tx1.send((Vec::new(), true));
-                }
-                Ok(_) => {
-                    let _ = tx1.send((stdout_buf, true));
-                }
-            }
+        let chunk_size = self.capture_buffer.min(CAPTURE_CHUNK_SIZE);
+        let (tx, rx) = mpsc::channel();
+        let capture = Arc::new(CaptureState {
+            capacity: self.capture_buffer,
+            max_output_bytes: self.max_output_bytes,
+            ..Default::default()
         });
+        let stdout = process.stdout.take();
+        let stderr = process.stderr.take();
 
-        // Spawn a thread to capture stderr asynchronously.
-        thread::spawn(move || {
-            let mut stderr_buf = Vec::new();
-            match stderr.read_to_end(&mut stderr_buf) {
-                Err(_) => {
-                    let _ = tx.send((Vec::new(), false));
-                }
-                Ok(_) => {
-                    let _ = tx.send((stderr_buf, false));
-                }
-            }
-        });
+        let seq = Arc::new(AtomicU64::new(0));
+        let tee = || match (self.tee, &self.tee_sink) {
+            (false, _) => Tee::Off,
+            (true, None) => Tee::Console,
+            (true, Some(sink)) => Tee::Buffer(sink.clone()),
+        };
+        // Streams that are not captured go to /dev/null and have no pipe to read.
+        self.capture_threads = Vec::new();
+        if let Some(stdout) = stdout {
+            self.capture_threads.push(spawn_capture(
+                stdout,
+                tx.clone(),
+                seq.clone(),
+                capture.clone(),
+                chunk_size,
+                tee(),
+                true,
+            ));
+        }
+        if let Some(stderr) = stderr {
+            self.capture_threads.push(spawn_capture(
+                stderr,
+                tx,
+                seq,
+                capture.clone(),
+                chunk_size,
+                tee(),
+                false,
+            ));
+        }
 
-        self.process = Some(Arc::new(Mutex::new(process)));
+        let process = Arc::new(Mutex::new(ChildProcess { child: process, exit: None }));
+        if let Some(children) = &self.children {
+            children.lock().unwrap().push(process.clone());
+        }
+
+        self.process = Some(process);
+        self.stdout.clear();
+        self.stderr.clear();
+        self.stdout_read_pos = 0;
+        self.combined.clear();
+        self.last_max_rss = None;
+        self.last_status = None;
+        self.capture = capture;
         self.rx = Some(rx);
 
         Ok(())
     }
 
     /// Waits for the process to complete and returns its output.
+    ///
+    /// If the timeout elapses first, the process is killed and the output captured so far is
+    /// returned in [`TesterError::WaitTimeoutWithOutput`]. If the process cannot be killed, e.g.
+    /// because it belongs to another user, [`TesterError::WaitTimeoutKillFailed`] is returned
+    /// instead and the process is left in place, since cleanup is incomplete.
     pub fn wait(&mut self) -> Result<(Vec<u8>, Vec<u8>, ExitStatus)> {
         let start = Instant::now();
-        let process = self.process.as_mut().ok_or(TesterError::NoProcessRunning)?;
-        let mut stdout = Vec::new();
-        let mut stderr = Vec::new();
+        let process = self.process.clone().ok_or(TesterError::NoProcessRunning)?;
 
         loop {
+            self.drain_output();
+
+            if self.capture.truncated.load(Ordering::SeqCst) {
+                // Stop a process whose output no longer fits rather than discard it forever.
+                self.kill()?;
+                let status = process
+                    .lock()
+                    .unwrap()
+                    .wait()
+                    .map_err(|e| TesterError::ProcessWaitFailed(e.to_string()))?;
+                self.last_status = Some(status);
+                return Ok((self.stdout.clone(), self.stderr.clone(), status));
+            }
+
             let status = {
                 let mut process = process.lock().unwrap();
-                process.try_wait()?
+                process.try_wait_with_usage()?
             };
 
-            if let Some(status) = status {
-                if let Some(rx) = &self.rx {
-                    for (buf, is_stdout) in rx.try_iter() {
-                        if is_stdout {
-                            stdout = buf;
-                        } else {
-                            stderr = buf;
-                        }
-                    }
-                }
+            if let Some((status, usage)) = status {
+                self.last_max_rss = usage.map(|usage| usage.max_rss);
+                self.last_status = Some(status);
+                self.drain_until_closed();
                 self.process = None;
-                return Ok((stdout, stderr, status));
+
+                if let Some(reason) = self.exceeded_resource_limit(status, usage) {
+                    return Err(TesterError::ResourceLimitExceeded(reason));
+                }
+
+                return Ok((self.stdout.clone(), self.stderr.clone(), status));
             }
 
             if start.elapsed() > self.timeout {
-                self.kill()?;
-                return Err(TesterError::WaitTimeout(self.timeout));
+                if let Err(err) = self.kill() {
+                    let reason = match err {
+                        TesterError::ProcessKillFailed(reason) => reason,
+                        err => err.to_string(),
+                    };
+                    return Err(TesterError::WaitTimeoutKillFailed {
+                        timeout: self.timeout,
+                        reason,
+                    });
+                }
+                self.drain_until_closed();
+                return Err(TesterError::WaitTimeoutWithOutput {
+                    timeout: self.timeout,
+                    stdout: self.stdout.clone(),
+                    stderr: self.stderr.clone(),
+                });
+            }
+
+            // Keep collecting between status checks so a chatty process doesn't fill the buffer.
+            self.collect_output_for(self.poll_interval);
+        }
+    }
+
+    /// Waits for the process to complete like [`Executable::wait`], but reports how it ended as
+    /// an [`ExitReason`]. A timeout is reported as [`ExitReason::TimedOut`] along with the output
+    /// captured before the process was killed.
+    pub fn wait_with_reason(&mut self) -> Result<(Vec<u8>, Vec<u8>, ExitReason)> {
+        match self.wait() {
+            Ok((stdout, stderr, status)) => Ok((stdout, stderr, status.into())),
+            Err(TesterError::WaitTimeoutWithOutput { stdout, stderr, .. }) => {
+                Ok((stdout, stderr, ExitReason::TimedOut))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Waits for the process to complete like [`Executable::wait`], but also treats an
+    /// unsuccessful exit as an error, reported with the tail of the process's stderr.
+    pub fn wait_success(&mut self) -> Result<(Vec<u8>, Vec<u8>)> {
+        let (stdout, stderr, status) = self.wait()?;
+        if !status.success() {
+            return Err(TesterError::ProcessFailed {
+                status,
+                stderr_tail: String::from_utf8_lossy(self.last_stderr()).into_owned(),
+            });
+        }
+
+        Ok((stdout, stderr))
+    }
+
+    /// Returns the last few lines of the stderr captured so far, which usually hold the panic
+    /// message or stack trace of a crashed program.
+    pub fn last_stderr(&self) -> &[u8] {
+        tail_lines(&self.stderr, STDERR_TAIL_LINES)
+    }
+
+    /// Returns the captured output as `(is_stdout, bytes)` chunks ordered by arrival.
+    ///
+    /// Empty unless enabled with [`Executable::with_combined_output`]. Only includes chunks
+    /// already collected by `wait`, `read_until` or an incremental read.
+    pub fn combined_output(&self) -> Vec<(bool, Vec<u8>)> {
+        let mut chunks = self.combined.clone();
+        chunks.sort_by_key(|(seq, _, _)| *seq);
+        chunks.into_iter().map(|(_, is_stdout, data)| (is_stdout, data)).collect()
+    }
+
+    /// Returns the stdout bytes captured since the previous incremental read, without waiting
+    /// for the process to exit.
+    pub fn read_available_stdout(&mut self) -> Result<Vec<u8>> {
+        if self.rx.is_none() {
+            return Err(TesterError::NoProcessRunning);
+        }
+
+        self.drain_output();
+        let available = self.stdout[self.stdout_read_pos..].to_vec();
+        self.stdout_read_pos = self.stdout.len();
+
+        Ok(available)
+    }
+
+    /// Reads stdout until `needle` appears, returning everything read up to and including it.
+    ///
+    /// Bytes after the needle stay buffered for subsequent reads. Fails with
+    /// [`TesterError::WaitTimeout`] if the needle doesn't appear within `timeout`.
+    pub fn read_until(&mut self, needle: &[u8], timeout: Duration) -> Result<Vec<u8>> {
+        self.read_stdout_with(timeout, |unread| {
+            find_subslice(unread, needle).map(|pos| pos + needle.len())
+        })
+    }
+
+    /// Reads exactly `n` bytes from stdout, e.g. a length-prefixed frame.
+    ///
+    /// Any further bytes stay buffered for subsequent reads. Fails with
+    /// [`TesterError::WaitTimeout`] if `n` bytes aren't available within `timeout`.
+    pub fn read_exact_stdout(&mut self, n: usize, timeout: Duration) -> Result<Vec<u8>> {
+        self.read_stdout_with(timeout, |unread| (unread.len() >= n).then_some(n))
+    }
+
+    /// Reads stdout until `complete` reports how many of the unread bytes make up the result.
+    fn read_stdout_with<F>(&mut self, timeout: Duration, complete: F) -> Result<Vec<u8>>
+    where
+        F: Fn(&[u8]) -> Option<usize>,
+    {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            self.drain_output();
+
+            if let Some(len) = complete(&self.stdout[self.stdout_read_pos..]) {
+                let end = self.stdout_read_pos + len;
+                let read = self.stdout[self.stdout_read_pos..end].to_vec();
+                self.stdout_read_pos = end;
+                return Ok(read);
             }
 
-            thread::sleep(Duration::from_millis(100));
+            let rx = self.rx.as_ref().ok_or(TesterError::NoProcessRunning)?;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(remaining) {
+                Ok(chunk) => self.store_chunk(chunk),
+                Err(RecvTimeoutError::Timeout) => return Err(TesterError::WaitTimeout(timeout)),
+                Err(RecvTimeoutError::Disconnected) => return Err(TesterError::OutputClosed),
+            }
+        }
+    }
+
+    /// Moves every chunk already sent by the capture threads into the output buffers.
+    fn drain_output(&mut self) {
+        let chunks: Vec<Chunk> = match &self.rx {
+            Some(rx) => rx.try_iter().collect(),
+            None => return,
+        };
+        for chunk in chunks {
+            self.store_chunk(chunk);
+        }
+    }
+
+    /// Collects output as it arrives for `duration`.
+    fn collect_output_for(&mut self, duration: Duration) {
+        let deadline = Instant::now() + duration;
+        while let Some(rx) = &self.rx {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(remaining) {
+                Ok(chunk) => self.store_chunk(chunk),
+                Err(RecvTimeoutError::Timeout) => return,
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        thread::sleep(deadline.saturating_duration_since(Instant::now()));
+    }
+
+    /// Collects output until both capture threads have reached the end of their streams, giving
+    /// up after [`OUTPUT_DRAIN_GRACE`].
+    ///
+    /// A process that has exited may still have output in flight, so draining only what has
+    /// already arrived could lose its last writes.
+    fn drain_until_closed(&mut self) {
+        let deadline = Instant::now() + OUTPUT_DRAIN_GRACE;
+        while let Some(rx) = &self.rx {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(remaining) {
+                Ok(chunk) => self.store_chunk(chunk),
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// Disconnects the previous run's capture threads and joins them once they have exited.
+    ///
+    /// Threads exit as soon as the channel is gone or their pipe reaches EOF. A thread that is
+    /// still blocked after `OUTPUT_DRAIN_GRACE`, because a process the child spawned keeps its
+    /// pipe open, is left to exit on its own.
+    fn reap_capture_threads(&mut self) {
+        self.rx = None;
+
+        let deadline = Instant::now() + OUTPUT_DRAIN_GRACE;
+        for handle in self.capture_threads.drain(..) {
+            while !handle.is_finished() && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(1));
+            }
+            if handle.is_finished() {
+                let _ = handle.join();
+            }
+        }
+    }
+
+    /// Appends a captured chunk to its output buffer, and to the merged stream if enabled.
+    fn store_chunk(&mut self, chunk: Chunk) {
+        self.capture.in_flight.fetch_sub(chunk.data.len(), Ordering::SeqCst);
+
+        let buf = if chunk.is_stdout { &mut self.stdout } else { &mut self.stderr };
+        buf.extend_from_slice(&chunk.data);
+        if self.combine_output {
+            self.combined.push((chunk.seq, chunk.is_stdout, chunk.data));
         }
     }
 
-    /// Kills the process.
+    /// Writes a line to the stdin of the running process, appending a newline and flushing.
+    ///
+    /// See [`Executable::write_stdin`] for how the timeout applies.
+    pub fn write_line(&mut self, line: &str) -> Result<()> {
+        self.write_stdin(format!("{line}\n").as_bytes())
+    }
+
+    /// Writes `data` to the stdin of the running process and flushes it.
+    ///
+    /// A process that stops reading its input can block the write. If the write does not
+    /// complete within the timeout, [`TesterError::WaitTimeout`] is returned and the process's
+    /// stdin is no longer available for writing.
+    pub fn write_stdin(&mut self, data: &[u8]) -> Result<()> {
+        let process = self.process.as_ref().ok_or(TesterError::NoProcessRunning)?;
+        let mut stdin =
+            process.lock().unwrap().child.stdin.take().ok_or(TesterError::StdinCaptureFailed)?;
+
+        let data = data.to_vec();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let result = stdin.write_all(&data).and_then(|_| stdin.flush());
+            let _ = tx.send((stdin, result));
+        });
+
+        match rx.recv_timeout(self.timeout) {
+            Ok((stdin, result)) => {
+                process.lock().unwrap().child.stdin = Some(stdin);
+                result.map_err(|e| TesterError::io_with_context(e, "failed to write to stdin"))
+            }
+            Err(_) => Err(TesterError::WaitTimeout(self.timeout)),
+        }
+    }
+
+    /// Closes the stdin of the running process, signaling EOF to it.
+    ///
+    /// The process keeps running, so output it produces after reaching the end of its input
+    /// can still be read or waited for. Later writes fail with [`TesterError::StdinCaptureFailed`].
+    pub fn close_stdin(&mut self) -> Result<()> {
+        let process = self.process.as_ref().ok_or(TesterError::NoProcessRunning)?;
+        drop(process.lock().unwrap().child.stdin.take());
+
+        Ok(())
+    }
+
+    /// Splits the running process into a [`StdinWriter`] and a [`StdoutReader`] for dialogs where
+    /// the next input depends on earlier output.
+    ///
+    /// The writer takes ownership of the process's stdin, so it can be moved to another thread
+    /// while the reader waits for output, and later writes through this executable fail with
+    /// [`TesterError::StdinCaptureFailed`]. The reader borrows this executable and reads from the
+    /// output its capture threads collect, so stdout is never consumed twice; once it is dropped,
+    /// the executable can be waited for as usual.
+    pub fn interact(&mut self) -> Result<(StdinWriter, StdoutReader<'_>)> {
+        let process = self.process.as_ref().ok_or(TesterError::NoProcessRunning)?;
+        let stdin =
+            process.lock().unwrap().child.stdin.take().ok_or(TesterError::StdinCaptureFailed)?;
+
+        Ok((StdinWriter::new(stdin), StdoutReader::new(self)))
+    }
+
+    /// Applies the working directory, environment and output capture settings to a command.
+    fn configure(&self, cmd: &mut Command) {
+        if !self.capture_stdout {
+            cmd.stdout(Stdio::null());
+        }
+        if !self.capture_stderr {
+            cmd.stderr(Stdio::null());
+        }
+
+        if let Some(dir) = &self.working_dir {
+            cmd.current_dir(dir);
+        }
+
+        if self.clear_env {
+            cmd.env_clear();
+        }
+        cmd.envs(&self.env);
+
+        #[cfg(unix)]
+        self.apply_resource_limits(cmd);
+    }
+
+    /// Applies the configured resource limits to the process before it executes.
+    #[cfg(unix)]
+    fn apply_resource_limits(&self, cmd: &mut Command) {
+        use std::os::unix::process::CommandExt;
+
+        let memory_limit = self.memory_limit;
+        let cpu_time_limit = self.cpu_time_limit.map(|limit| limit.as_secs().max(1));
+        if memory_limit.is_none() && cpu_time_limit.is_none() {
+            return;
+        }
+
+        let set_limit = |resource, soft: u64, hard: u64| {
+            let limit =
+                libc::rlimit { rlim_cur: soft as libc::rlim_t, rlim_max: hard as libc::rlim_t };
+            // SAFETY: `setrlimit` only reads the provided struct and is async-signal-safe.
+            if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        };
+
+        // SAFETY: the closure only performs async-signal-safe system calls.
+        unsafe {
+            cmd.pre_exec(move || {
+                if let Some(bytes) = memory_limit {
+                    set_limit(libc::RLIMIT_AS, bytes, bytes)?;
+                }
+                // The soft limit delivers SIGXCPU; the hard limit one second later kills the
+                // process if it handles that signal.
+                if let Some(seconds) = cpu_time_limit {
+                    set_limit(libc::RLIMIT_CPU, seconds, seconds + 1)?;
+                }
+                Ok(())
+            });
+        }
+    }
+
+    /// Describes the resource limit the process exceeded, if the OS or the process itself
+    /// reported it.
+    ///
+    /// A crash alone is not attributed to a limit: the CPU time limit counts as exceeded on
+    /// `SIGXCPU` or once the CPU time used reaches it, and the memory limit once the peak
+    /// resident set size reaches it or the process reported on stderr that an allocation failed.
+    fn exceeded_resource_limit(
+        &self,
+        status: ExitStatus,
+        usage: Option<ResourceUsage>,
+    ) -> Option<String> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+
+            if let Some(limit) = self.cpu_time_limit {
+                // The limit applies in whole seconds; see `apply_resource_limits`.
+                let applied = Duration::from_secs(limit.as_secs().max(1));
+                if status.signal() == Some(libc::SIGXCPU) ||
+                    usage.is_some_and(|usage| usage.cpu_time >= applied)
+                {
+                    return Some(format!("process exceeded its CPU time limit of {limit:?}"));
+                }
+            }
+            if let Some(bytes) = self.memory_limit {
+                if usage.is_some_and(|usage| usage.max_rss >= bytes) {
+                    return Some(format!("process reached its memory limit of {bytes} bytes"));
+                }
+                if !status.success() && reports_allocation_failure(self.last_stderr()) {
+                    return Some(format!(
+                        "process failed to allocate memory within its memory limit of {bytes} \
+                         bytes"
+                    ));
+                }
+            }
+        }
+
+        #[cfg(not(unix))]
+        let _ = (status, usage);
+
+        None
+    }
+
+    /// Kills the process, then collects the output it produced before it died so that it is
+    /// still available from [`Executable::captured_stdout`] and [`Executable::captured_stderr`].
     pub fn kill(&mut self) -> Result<()> {
         if let Some(process) = &self.process {
-            let mut process = process.lock().unwrap();
-            process.kill().map_err(|e| TesterError::ProcessKillFailed(e.to_string()))?;
+            process.lock().unwrap().kill()?;
+            self.drain_until_closed();
         }
         self.process = None;
 
         Ok(())
     }
 
+    /// Returns the exit status of the last process that [`Executable::wait`] saw exit, or `None`
+    /// while it is running or if it was killed instead.
+    ///
+    /// Like the output from [`Executable::captured_stdout`] and [`Executable::last_stderr`], it
+    /// stays available after `wait` has returned, until the next process is started.
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        self.last_status
+    }
+
+    /// Returns the peak resident set size, in bytes, of the last process that [`Executable::wait`]
+    /// saw exit, including the descendants it waited for.
+    ///
+    /// Only available on Unix. The OS reports the value in kilobytes on Linux and in bytes on
+    /// macOS; it is converted to bytes either way, so it is only as precise as the OS unit.
+    pub fn last_max_rss(&self) -> Option<u64> {
+        self.last_max_rss
+    }
+
+    /// Returns whether output of the most recently started process was discarded because it
+    /// exceeded the limit set with [`Executable::with_max_output_bytes`], or arrived while the
+    /// buffer set with [`Executable::with_capture_buffer`] was full.
+    pub fn output_truncated(&self) -> bool {
+        self.capture.truncated.load(Ordering::SeqCst) || self.capture.dropped.load(Ordering::SeqCst)
+    }
+
+    /// Returns all stdout captured from the most recently started process so far.
+    pub fn captured_stdout(&self) -> &[u8] {
+        &self.stdout
+    }
+
+    /// Returns all stderr captured from the most recently started process so far.
+    pub fn captured_stderr(&self) -> &[u8] {
+        &self.stderr
+    }
+
+    /// Returns the stdout captured from the most recently started process as a string, failing
+    /// with the byte offset of the first invalid sequence if it is not valid UTF-8.
+    ///
+    /// Unlike the lossy conversion of [`Executable::run_text`], this reports malformed output,
+    /// e.g. for challenges that require the program to print valid UTF-8.
+    pub fn stdout_utf8(&self) -> Result<String> {
+        String::from_utf8(self.stdout.clone()).map_err(|err| {
+            let offset = err.utf8_error().valid_up_to();
+            TesterError::assertion(format!(
+                "stdout is not valid UTF-8: invalid byte 0x{:02x} at byte offset {offset} \
+                 (0x{offset:x})",
+                self.stdout[offset]
+            ))
+        })
+    }
+
+    /// Sends `signal` (e.g. `libc::SIGHUP`) to the process without waiting for it to react.
+    ///
+    /// Unlike [`Executable::kill`], only the process itself is signaled, not its children.
+    #[cfg(unix)]
+    pub fn send_signal(&self, signal: i32) -> Result<()> {
+        let pid = self.pid().ok_or(TesterError::NoProcessRunning)?;
+
+        // SAFETY: `kill` has no memory-safety preconditions.
+        if unsafe { libc::kill(pid as libc::pid_t, signal) } != 0 {
+            let err = std::io::Error::last_os_error();
+            if err.raw_os_error() == Some(libc::ESRCH) {
+                return Err(TesterError::NoProcessRunning);
+            }
+            return Err(TesterError::io_with_context(
+                err,
+                &format!("failed to send signal {signal}"),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Gracefully stops the process, giving it up to `grace` to exit on its own before it is
+    /// killed.
+    ///
+    /// On Unix the process first receives `SIGTERM`; elsewhere it is killed right away.
+    pub fn shutdown(&mut self, grace: Duration) -> Result<ExitStatus> {
+        let process = self.process.clone().ok_or(TesterError::NoProcessRunning)?;
+
+        #[cfg(unix)]
+        {
+            let pid = {
+                let mut process = process.lock().unwrap();
+                if let Some(status) = process.try_wait()? {
+                    self.process = None;
+                    return Ok(status);
+                }
+                process.id()
+            };
+
+            signal_process_group(pid, libc::SIGTERM)?;
+
+            let deadline = Instant::now() + grace;
+            while Instant::now() < deadline {
+                if let Some(status) = process.lock().unwrap().try_wait()? {
+                    // Don't leave behind any processes the child spawned.
+                    signal_process_group(pid, libc::SIGKILL)?;
+                    self.process = None;
+                    return Ok(status);
+                }
+                thread::sleep(self.poll_interval);
+            }
+        }
+
+        #[cfg(not(unix))]
+        let _ = grace;
+
+        let status = {
+            let mut process = process.lock().unwrap();
+            process.kill()?;
+            process.wait().map_err(|e| TesterError::ProcessWaitFailed(e.to_string()))?
+        };
+        self.process = None;
+
+        Ok(status)
+    }
+
     /// Non-blocking check for process status.
     pub fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
         if let Some(process) = &self.process {
@@ -248,3 +1236,295 @@ impl Executable {
         }
     }
 }
+
+/// Spawns a thread that forwards everything read from `reader` to `tx` in chunks of at most
+/// `chunk_size` bytes, tagging each chunk with whether it came from stdout. Each chunk is also
+/// forwarded as `tee` directs. Returns the handle of the thread.
+///
+/// The thread reads until the end of the stream without ever blocking on `tx`. Output past the
+/// output limit of `state`, or that would take the output in flight past its capacity, is
+/// dropped and recorded there.
+fn spawn_capture<R: Read + Send + 'static>(
+    mut reader: R,
+    tx: mpsc::Sender<Chunk>,
+    seq: Arc<AtomicU64>,
+    state: Arc<CaptureState>,
+    chunk_size: usize,
+    tee: Tee,
+    is_stdout: bool,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut buf = vec![0; chunk_size];
+        let mut kept = 0;
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => {
+                    let n = state
+                        .max_output_bytes
+                        .map_or(read, |max| read.min(max.saturating_sub(kept)));
+                    kept += n;
+
+                    if n > 0 {
+                        // Echoing is best effort; a closed stream must not stop the capture.
+                        let _ = match &tee {
+                            Tee::Off => Ok(()),
+                            Tee::Console if is_stdout => {
+                                std::io::stdout().lock().write_all(&buf[..n])
+                            }
+                            Tee::Console => std::io::stderr().lock().write_all(&buf[..n]),
+                            Tee::Buffer(sink) => {
+                                sink.lock().unwrap().extend_from_slice(&buf[..n]);
+                                Ok(())
+                            }
+                        };
+
+                        let queued = state.in_flight.fetch_add(n, Ordering::SeqCst);
+                        if queued + n > state.capacity {
+                            state.in_flight.fetch_sub(n, Ordering::SeqCst);
+                            state.dropped.store(true, Ordering::SeqCst);
+                        } else {
+                            let seq = seq.fetch_add(1, Ordering::SeqCst);
+                            let chunk = Chunk { seq, data: buf[..n].to_vec(), is_stdout };
+                            if tx.send(chunk).is_err() {
+                                break;
+                            }
+                        }
+                    }
+
+                    // Past the limit, keep reading so the process never blocks on a full pipe,
+                    // but discard what is read.
+                    if n < read {
+                        state.truncated.store(true, Ordering::SeqCst);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        }
+    })
+}
+
+impl ChildProcess {
+    /// Returns the OS-assigned process id.
+    fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Reaps the process if it has exited, without blocking.
+    fn try_wait(&mut self) -> std::io::Result<Option<ExitStatus>> {
+        Ok(self.try_wait_with_usage()?.map(|(status, _)| status))
+    }
+
+    /// Like [`ChildProcess::try_wait`], but also returns the resources the process used where
+    /// the OS reports them.
+    fn try_wait_with_usage(
+        &mut self,
+    ) -> std::io::Result<Option<(ExitStatus, Option<ResourceUsage>)>> {
+        if self.exit.is_none() {
+            self.exit = reap(&mut self.child, false)?;
+        }
+        Ok(self.exit)
+    }
+
+    /// Blocks until the process has exited and reaps it.
+    fn wait(&mut self) -> std::io::Result<ExitStatus> {
+        if self.exit.is_none() {
+            self.exit = reap(&mut self.child, true)?;
+        }
+        Ok(self.exit.expect("a blocking wait reaps the process").0)
+    }
+
+    /// Forcibly kills the process, along with its whole process group on Unix. Does nothing once
+    /// the process has been reaped.
+    fn kill(&mut self) -> Result<()> {
+        if self.exit.is_some() {
+            return Ok(());
+        }
+
+        #[cfg(unix)]
+        {
+            signal_process_group(self.id(), libc::SIGKILL)
+        }
+
+        #[cfg(not(unix))]
+        {
+            self.child.kill().map_err(|e| TesterError::ProcessKillFailed(e.to_string()))
+        }
+    }
+}
+
+/// Reaps `child` if it has exited, or once it exits if `block` is set, returning its exit status
+/// and the resources it used where the OS reports them.
+fn reap(
+    child: &mut Child,
+    block: bool,
+) -> std::io::Result<Option<(ExitStatus, Option<ResourceUsage>)>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+
+        let flags = if block { 0 } else { libc::WNOHANG };
+        let mut status = 0;
+        // SAFETY: `rusage` is plain data, for which all zeroes is a valid value.
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        loop {
+            // SAFETY: `status` and `usage` are valid for writes for the duration of the call.
+            let pid =
+                unsafe { libc::wait4(child.id() as libc::pid_t, &mut status, flags, &mut usage) };
+
+            match pid {
+                0 => return Ok(None),
+                -1 => {
+                    let err = std::io::Error::last_os_error();
+                    if err.raw_os_error() != Some(libc::EINTR) {
+                        return Err(err);
+                    }
+                }
+                _ => {
+                    let max_rss = u64::try_from(usage.ru_maxrss).unwrap_or(0);
+                    let max_rss =
+                        if cfg!(target_vendor = "apple") { max_rss } else { max_rss * 1024 };
+                    let cpu_time =
+                        timeval_duration(usage.ru_utime) + timeval_duration(usage.ru_stime);
+                    let usage = ResourceUsage { max_rss, cpu_time };
+                    return Ok(Some((ExitStatus::from_raw(status), Some(usage))));
+                }
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let status = if block { Some(child.wait()?) } else { child.try_wait()? };
+        Ok(status.map(|status| (status, None)))
+    }
+}
+
+/// Converts a `timeval` reported by the OS to a `Duration`.
+#[cfg(unix)]
+fn timeval_duration(time: libc::timeval) -> Duration {
+    Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+}
+
+/// Returns whether `stderr` holds a message common runtimes print when an allocation fails.
+#[cfg(unix)]
+fn reports_allocation_failure(stderr: &[u8]) -> bool {
+    const MESSAGES: [&str; 6] = [
+        "cannot allocate",
+        "memory allocation of",
+        "memory exhausted",
+        "out of memory",
+        "bad_alloc",
+        "memoryerror",
+    ];
+
+    let stderr = String::from_utf8_lossy(stderr).to_lowercase();
+    MESSAGES.iter().any(|message| stderr.contains(message))
+}
+
+/// Kills and reaps every process recorded in `children` that is still running.
+pub(crate) fn kill_children(children: &ChildRegistry) {
+    for process in children.lock().unwrap().drain(..) {
+        let mut process = process.lock().unwrap();
+        if matches!(process.try_wait(), Ok(None)) && process.kill().is_ok() {
+            let _ = process.wait();
+        }
+    }
+}
+
+/// Sends `signal` to the process group led by the child with the given pid.
+///
+/// A group that no longer exists is not an error, since there is nothing left to signal.
+#[cfg(unix)]
+fn signal_process_group(pid: u32, signal: libc::c_int) -> Result<()> {
+    // SAFETY: `killpg` has no memory-safety preconditions.
+    if unsafe { libc::killpg(pid as libc::pid_t, signal) } != 0 {
+        let err = std::io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::ESRCH) {
+            return Err(TesterError::ProcessKillFailed(err.to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Maps a failure to spawn the program at `path` to the most specific error available.
+fn spawn_error(path: &Path, err: std::io::Error) -> TesterError {
+    #[cfg(unix)]
+    if err.raw_os_error() == Some(libc::ENOEXEC) {
+        return TesterError::ExecFormatError(path.to_path_buf());
+    }
+
+    match err.kind() {
+        ErrorKind::PermissionDenied => TesterError::ExecutableNotExecutable(path.to_path_buf()),
+        // The program itself exists, so it is the interpreter named by its shebang that is missing.
+        ErrorKind::NotFound if path.is_file() => {
+            TesterError::InterpreterNotFound(path.to_path_buf())
+        }
+        _ => TesterError::ProcessExecution(err.to_string()),
+    }
+}
+
+/// Returns the last `count` lines of `bytes`, not counting a trailing newline as a line break.
+pub(crate) fn tail_lines(bytes: &[u8], count: usize) -> &[u8] {
+    let body = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    let start = body
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, byte)| **byte == b'\n')
+        .nth(count.saturating_sub(1))
+        .map_or(0, |(index, _)| index + 1);
+    &bytes[start..]
+}
+
+/// Returns the position of the first occurrence of `needle` in `haystack`.
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// Checks whether the file at `path` exists and can be executed.
+///
+/// On Unix this requires at least one of the execute permission bits to be set. On Windows the
+/// extension must be listed in `PATHEXT`, or the file must start with an `MZ` executable header.
+pub(crate) fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        path.metadata().is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    }
+
+    #[cfg(windows)]
+    {
+        if !path.is_file() {
+            return false;
+        }
+
+        let pathext =
+            std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+        let has_executable_extension = path.extension().is_some_and(|ext| {
+            let ext = format!(".{}", ext.to_string_lossy());
+            pathext.split(';').any(|candidate| candidate.eq_ignore_ascii_case(&ext))
+        });
+
+        has_executable_extension || has_executable_header(path)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        path.exists()
+    }
+}
+
+/// Checks whether the file at `path` starts with the `MZ` magic of a DOS/PE executable.
+#[cfg(windows)]
+fn has_executable_header(path: &Path) -> bool {
+    let mut magic = [0; 2];
+    std::fs::File::open(path).and_then(|mut file| file.read_exact(&mut magic)).is_ok() &&
+        &magic == b"MZ"
+}
@@ -13,27 +13,132 @@
 // limitations under the License.
 
 use std::{
-    fmt,
-    sync::{Arc, Mutex},
+    collections::HashMap,
+    fmt, fs,
+    io::{self, ErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket},
+    path::PathBuf,
+    sync::{
+        Arc, Mutex, OnceLock,
+        mpsc::{self, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
-use crate::executable::Executable;
+use rand::{SeedableRng, rngs::StdRng};
+use serde_json::{Number, Value};
+use tracing::{Dispatch, dispatcher};
+
+use crate::{
+    CaseError, HelperHandle, HttpResponse, Logger, Result, TesterError,
+    executable::{self, ChildRegistry, Executable},
+    hexdump::{HEXDUMP_WIDTH, hexdump_row},
+    normalize_newlines, resolve_within,
+};
+
+/// How often [`Harness::assert_running_for`] checks on the process.
+const RUNNING_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 /// Alias for a thread-safe collection of teardown functions.
 type TeardownFuncs = Arc<Mutex<Vec<Box<dyn FnOnce() + Send>>>>;
 
+/// The random seed of a run, chosen by the first [`Harness::rng`] call and shared by every
+/// harness of the run.
+pub(crate) type SeedCell = Arc<OnceLock<u64>>;
+
 #[derive(Clone)]
 pub struct Harness {
     /// Executable is the program to be tested.
     executable: Executable,
     /// Teardown functions are run once the test has completed.
     teardown_funcs: TeardownFuncs,
+    /// Log prefix of the test step this harness belongs to (e.g., `"stage-1"`).
+    log_prefix: String,
+    /// Directory holding the submission (`STACKCLASS_REPOSITORY_DIR`), if known.
+    repository_dir: Option<PathBuf>,
+    /// Environment variables the tester was started with.
+    env: Arc<HashMap<String, String>>,
+    /// Processes started by executables from [`Harness::spawn_executable`].
+    children: ChildRegistry,
+    /// Seed of the generators returned by [`Harness::rng`], once chosen.
+    seed: SeedCell,
 }
 
 impl Harness {
     /// Creates a new `Harness` with the provided executable.
     pub fn new(executable: Executable) -> Self {
-        Harness { executable, teardown_funcs: Arc::new(Mutex::new(Vec::new())) }
+        Harness {
+            executable,
+            teardown_funcs: Arc::new(Mutex::new(Vec::new())),
+            log_prefix: String::new(),
+            repository_dir: None,
+            env: Arc::default(),
+            children: Arc::default(),
+            seed: Arc::default(),
+        }
+    }
+
+    /// Sets the log prefix of the test step this harness belongs to.
+    pub fn with_log_prefix<S: Into<String>>(mut self, log_prefix: S) -> Self {
+        self.log_prefix = log_prefix.into();
+        self
+    }
+
+    /// Sets the submission directory that relative paths in file helpers are resolved against.
+    pub fn with_repository_dir<P: Into<PathBuf>>(mut self, repository_dir: P) -> Self {
+        self.repository_dir = Some(repository_dir.into());
+        self
+    }
+
+    /// Sets the environment variables that [`Harness::env`] reads from.
+    pub fn with_env(mut self, env: Arc<HashMap<String, String>>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Shares the random seed of [`Harness::rng`] with the other harnesses of a run.
+    pub(crate) fn with_seed(mut self, seed: SeedCell) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Returns the value of an environment variable the tester was started with, such as
+    /// `STACKCLASS_RANDOM_SEED`.
+    pub fn env(&self, key: &str) -> Option<&str> {
+        self.env.get(key).map(String::as_str)
+    }
+
+    /// Returns a random number generator seeded from `STACKCLASS_RANDOM_SEED`.
+    ///
+    /// Without a valid seed, a random one is chosen and logged by the first call of the run, and
+    /// every later call, in this step or another, uses it too. A failing run can therefore be
+    /// reproduced by exporting the logged seed.
+    pub fn rng(&self) -> StdRng {
+        let seed = *self.seed.get_or_init(|| {
+            match self.env("STACKCLASS_RANDOM_SEED").map(|seed| seed.trim().parse()) {
+                Some(Ok(seed)) => seed,
+                Some(Err(_)) => {
+                    let seed = rand::random();
+                    self.logger().errorf(format_args!(
+                        "Ignoring invalid STACKCLASS_RANDOM_SEED, using STACKCLASS_RANDOM_SEED={seed}"
+                    ));
+                    seed
+                }
+                None => {
+                    let seed = rand::random();
+                    self.logger().infof(format_args!("Using STACKCLASS_RANDOM_SEED={seed}"));
+                    seed
+                }
+            }
+        });
+
+        StdRng::seed_from_u64(seed)
+    }
+
+    /// Returns a logger that tags every line with the test step's log prefix.
+    pub fn logger(&self) -> Logger {
+        Logger::new(self.log_prefix.clone())
     }
 
     /// Registers a teardown function to be executed after the test completes.
@@ -45,12 +150,82 @@ impl Harness {
         funcs.push(Box::new(teardown_func));
     }
 
-    /// Runs all registered teardown functions.
+    /// Runs all registered teardown functions, then kills any process still running that was
+    /// started from [`Harness::spawn_executable`].
     pub fn run_teardown_funcs(&self) {
         let mut funcs = self.teardown_funcs.lock().unwrap();
         while let Some(func) = funcs.pop() {
             func();
         }
+        executable::kill_children(&self.children);
+    }
+
+    /// Makes executables from [`Harness::new_executable`] record the processes they start like
+    /// those from [`Harness::spawn_executable`], so that [`Harness::kill_processes`] covers them.
+    pub(crate) fn tracking_all_processes(mut self) -> Self {
+        self.executable = self.executable.tracked_copy(self.children.clone());
+        self
+    }
+
+    /// Kills every process still running that was started from a tracked executable.
+    pub(crate) fn kill_processes(&self) {
+        executable::kill_children(&self.children);
+    }
+
+    /// Runs all registered teardown functions like [`Harness::run_teardown_funcs`], but each on
+    /// its own thread. A teardown function still running after `per_func` is abandoned with a
+    /// warning, so that one that hangs, e.g. joining a stuck thread, cannot wedge the run.
+    pub fn run_teardown_funcs_with_timeout(&self, per_func: Duration) {
+        let funcs = std::mem::take(&mut *self.teardown_funcs.lock().unwrap());
+        let logger = self.logger();
+        // Keep logging to the caller's subscriber, which may be collecting the step's output.
+        let dispatch = dispatcher::get_default(Dispatch::clone);
+
+        for func in funcs.into_iter().rev() {
+            let (tx, rx) = mpsc::channel();
+            let dispatch = dispatch.clone();
+            thread::spawn(move || {
+                dispatcher::with_default(&dispatch, func);
+                let _ = tx.send(());
+            });
+
+            match rx.recv_timeout(per_func) {
+                Ok(()) => {}
+                Err(RecvTimeoutError::Timeout) => logger.warnf(format_args!(
+                    "teardown function did not finish within {per_func:?}, abandoning it"
+                )),
+                Err(RecvTimeoutError::Disconnected) => logger.errorf("teardown function panicked"),
+            }
+        }
+        executable::kill_children(&self.children);
+    }
+
+    /// Runs `f` on a background thread, e.g. to serve a fake upstream the program connects to,
+    /// and registers a teardown function that stops and joins it.
+    ///
+    /// `f` receives a [`HelperHandle`] and must return soon after it reports being stopped, since
+    /// teardown waits for it. A helper that panics is logged rather than failing teardown.
+    pub fn spawn_helper<F>(&self, f: F) -> HelperHandle
+    where
+        F: FnOnce(HelperHandle) + Send + 'static,
+    {
+        let handle = HelperHandle::default();
+        let helper = handle.clone();
+        let thread = thread::spawn(move || {
+            f(helper.clone());
+            helper.finish();
+        });
+
+        let teardown_handle = handle.clone();
+        let logger = self.logger();
+        self.register_teardown_func(move || {
+            teardown_handle.stop();
+            if thread.join().is_err() {
+                logger.errorf("helper thread panicked");
+            }
+        });
+
+        handle
     }
 
     /// Returns a reference to the executable.
@@ -62,10 +237,466 @@ impl Harness {
     pub fn new_executable(&self) -> Executable {
         self.executable.clone()
     }
+
+    /// Creates an independent executable for the same program, e.g. to run a client against a
+    /// server started from another one. Processes it starts are killed on teardown.
+    pub fn spawn_executable(&self) -> Executable {
+        self.executable.tracked_copy(self.children.clone())
+    }
+
+    /// Connects to `addr` over TCP, retrying up to `retries` more times with `delay` between
+    /// attempts while the program under test boots.
+    pub fn connect_tcp(&self, addr: &str, retries: usize, delay: Duration) -> Result<TcpStream> {
+        let mut attempt = 0;
+        loop {
+            match TcpStream::connect(addr) {
+                Ok(stream) => return Ok(stream),
+                Err(err) if attempt >= retries => {
+                    let context =
+                        format!("failed to connect to {addr} after {} attempts", attempt + 1);
+                    return Err(TesterError::io_with_context(err, &context));
+                }
+                Err(_) => {
+                    attempt += 1;
+                    thread::sleep(delay);
+                }
+            }
+        }
+    }
+
+    /// Connects to `addr` over TLS and completes the handshake for `server_name`, accepting any
+    /// certificate the server presents, such as a self-signed one. Fails with
+    /// [`TesterError::ResponseTimeout`] if connecting or the handshake takes longer than
+    /// `timeout`, which also applies to later reads and writes on the stream.
+    ///
+    /// Requires the `tls` feature.
+    #[cfg(feature = "tls")]
+    pub fn connect_tls(
+        &self,
+        addr: &str,
+        server_name: &str,
+        timeout: Duration,
+    ) -> Result<impl Read + Write + use<>> {
+        crate::tls::connect(addr, server_name, timeout)
+    }
+
+    /// Returns the size in bytes of a file in the submission directory.
+    pub fn file_size(&self, relative_path: &str) -> Result<u64> {
+        let path = self.submission_path(relative_path)?;
+        let metadata = fs::metadata(&path).map_err(|err| {
+            TesterError::io_with_context(err, &format!("failed to stat {}", path.display()))
+        })?;
+        Ok(metadata.len())
+    }
+
+    /// Reads a file from the submission directory, e.g. to detect hard-coded answers.
+    pub fn read_submission_file(&self, relative_path: &str) -> Result<Vec<u8>> {
+        let path = self.submission_path(relative_path)?;
+        fs::read(&path).map_err(|err| {
+            TesterError::io_with_context(err, &format!("failed to read {}", path.display()))
+        })
+    }
+
+    /// Resolves `relative_path` against the submission directory, rejecting paths that point
+    /// outside it.
+    fn submission_path(&self, relative_path: &str) -> Result<PathBuf> {
+        let repository_dir = self.repository_dir.as_deref().ok_or_else(|| {
+            TesterError::InvalidConfig("no repository directory is configured".into())
+        })?;
+
+        resolve_within(repository_dir, relative_path)
+    }
+
+    /// Calls `f` up to `attempts` times, sleeping `delay` between failed attempts, and returns
+    /// the first success or the last error.
+    pub fn retry<T, F>(&self, attempts: usize, delay: Duration, f: F) -> Result<T, CaseError>
+    where
+        F: FnMut() -> Result<T, CaseError>,
+    {
+        retry_with(attempts, delay, 1, f)
+    }
+
+    /// Like [`Harness::retry`], but doubles the delay after each failed attempt.
+    pub fn retry_with_backoff<T, F>(
+        &self,
+        attempts: usize,
+        delay: Duration,
+        f: F,
+    ) -> Result<T, CaseError>
+    where
+        F: FnMut() -> Result<T, CaseError>,
+    {
+        retry_with(attempts, delay, 2, f)
+    }
+
+    /// Polls `f` every `interval` until it returns true, e.g. while a server converges to an
+    /// expected state. Fails if `f` is still false once `timeout` has passed.
+    ///
+    /// The timeout is independent of the case and process timeouts, but the case timeout still
+    /// applies to the case as a whole.
+    pub fn wait_until<F>(
+        &self,
+        mut f: F,
+        timeout: Duration,
+        interval: Duration,
+    ) -> Result<(), CaseError>
+    where
+        F: FnMut() -> bool,
+    {
+        let start = Instant::now();
+        loop {
+            if f() {
+                return Ok(());
+            }
+
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Err(TesterError::assertion(format!(
+                    "condition was not met within {timeout:?}"
+                ))
+                .into());
+            }
+            thread::sleep(interval.min(timeout - elapsed));
+        }
+    }
+
+    /// Asserts that the process started by `exe` keeps running for at least `duration`, e.g. that
+    /// a server does not crash while idle after startup.
+    ///
+    /// If the process exits early, the error reports how it exited along with the tail of its
+    /// stderr.
+    pub fn assert_running_for(
+        &self,
+        exe: &mut Executable,
+        duration: Duration,
+    ) -> Result<(), CaseError> {
+        let start = Instant::now();
+        loop {
+            if !exe.is_running() {
+                let elapsed = start.elapsed();
+                let exit = match exe.wait() {
+                    Ok((_, _, status)) => status.to_string(),
+                    Err(err) => err.to_string(),
+                };
+
+                let mut message = format!(
+                    "process exited after {elapsed:?} ({exit}), expected it to keep running for \
+                     {duration:?}"
+                );
+                let stderr = exe.last_stderr();
+                if !stderr.is_empty() {
+                    message.push_str(&format!(
+                        "\nstderr:\n{}",
+                        String::from_utf8_lossy(stderr).trim_end()
+                    ));
+                }
+                return Err(TesterError::assertion(message).into());
+            }
+
+            let elapsed = start.elapsed();
+            if elapsed >= duration {
+                return Ok(());
+            }
+            thread::sleep(RUNNING_POLL_INTERVAL.min(duration - elapsed));
+        }
+    }
+
+    /// Asks the OS for a free TCP port on the loopback interface.
+    ///
+    /// The port is released before it is returned, so another process could claim it before the
+    /// program under test binds it. This window is small, but callers should treat a bind failure
+    /// on the returned port as possible rather than impossible.
+    pub fn reserve_port(&self) -> Result<u16> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        Ok(listener.local_addr()?.port())
+    }
+
+    /// Sends `payload` as a single datagram to `target` from an ephemeral UDP socket and returns
+    /// the reply. Datagrams from any other address are ignored. Fails with
+    /// [`TesterError::ResponseTimeout`] if no reply arrives within `timeout`.
+    pub fn udp_roundtrip(
+        &self,
+        target: &str,
+        payload: &[u8],
+        timeout: Duration,
+    ) -> Result<Vec<u8>> {
+        let target = target.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(ErrorKind::InvalidInput, "address resolved to nothing")
+        })?;
+
+        let bind_addr: SocketAddr =
+            if target.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0; 16], 0).into() };
+        let socket = UdpSocket::bind(bind_addr)?;
+        socket.set_read_timeout(Some(timeout))?;
+        // Connecting makes the OS drop datagrams that don't come from `target`.
+        socket.connect(target)?;
+        socket.send(payload)?;
+
+        let mut buf = vec![0; 65535];
+        match socket.recv(&mut buf) {
+            Ok(len) => {
+                buf.truncate(len);
+                Ok(buf)
+            }
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                Err(TesterError::ResponseTimeout(timeout))
+            }
+            Err(err) => Err(TesterError::io_with_context(err, "failed to receive UDP response")),
+        }
+    }
+
+    /// Sends a `GET` request for `path` to the HTTP server at `addr` and returns the parsed
+    /// response. Fails with [`TesterError::ResponseTimeout`] if connecting, sending or receiving
+    /// takes longer than `timeout`.
+    pub fn http_get(&self, addr: &str, path: &str, timeout: Duration) -> Result<HttpResponse> {
+        let target = addr.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(ErrorKind::InvalidInput, "address resolved to nothing")
+        })?;
+
+        let is_timeout =
+            |err: &io::Error| matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut);
+        let map_err = |err: io::Error, context: &str| {
+            if is_timeout(&err) {
+                TesterError::ResponseTimeout(timeout)
+            } else {
+                TesterError::io_with_context(err, &format!("{context} {addr}"))
+            }
+        };
+
+        let mut stream = TcpStream::connect_timeout(&target, timeout)
+            .map_err(|err| map_err(err, "failed to connect to"))?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+
+        let request = format!("GET {path} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n");
+        stream.write_all(request.as_bytes()).map_err(|err| map_err(err, "failed to send to"))?;
+
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).map_err(|err| map_err(err, "failed to read from"))?;
+
+        HttpResponse::parse(&raw)
+    }
+
+    /// Asserts that `actual` stdout bytes equal `expected`.
+    ///
+    /// On mismatch, the error shows the offset of the first differing byte along with a
+    /// side-by-side hexdump of the region around it.
+    pub fn assert_stdout_eq(&self, expected: &[u8], actual: &[u8]) -> Result<(), CaseError> {
+        let Some(offset) = first_difference(expected, actual) else {
+            return Ok(());
+        };
+
+        let message = format!(
+            "stdout mismatch at byte offset {offset} (0x{offset:x}): expected {} bytes, got {} \
+             bytes\n{}",
+            expected.len(),
+            actual.len(),
+            hexdump_side_by_side(expected, actual, offset)
+        );
+
+        Err(TesterError::assertion(message).into())
+    }
+
+    /// Asserts that `actual` stdout equals `expected` like [`Harness::assert_stdout_eq`], after
+    /// converting `\r\n` line endings in both to `\n` with [`normalize_newlines`].
+    ///
+    /// This accepts output of submissions running on Windows. Use [`Harness::assert_stdout_eq`]
+    /// where the exact bytes, line endings included, are part of the expected behavior.
+    pub fn assert_stdout_eq_normalized(
+        &self,
+        expected: &str,
+        actual: &[u8],
+    ) -> Result<(), CaseError> {
+        self.assert_stdout_eq(&normalize_newlines(expected.as_bytes()), &normalize_newlines(actual))
+    }
+
+    /// Asserts that `actual` text equals `expected`.
+    ///
+    /// On mismatch, the error shows a line diff in which lines only in `expected` are prefixed
+    /// with `-` and lines only in `actual` with `+`.
+    pub fn assert_eq_text(&self, expected: &str, actual: &str) -> Result<(), CaseError> {
+        if expected == actual {
+            return Ok(());
+        }
+
+        let mut diff = line_diff(expected, actual);
+        if !diff.lines().any(|line| line.starts_with(['-', '+'])) {
+            diff.push_str("(the texts differ only in line endings)\n");
+        }
+        let message = format!("text mismatch (- expected, + actual)\n{diff}");
+
+        Err(TesterError::assertion(message).into())
+    }
+
+    /// Asserts that `actual` is a JSON document equal to `expected`, comparing values rather
+    /// than bytes, so key order and whitespace do not matter. Numbers are compared by value, so
+    /// `1` equals `1.0`.
+    ///
+    /// On mismatch, the error names the path of the first differing value (e.g.,
+    /// `$.users[1].name`).
+    pub fn assert_json_eq(&self, actual: &[u8], expected: Value) -> Result<(), CaseError> {
+        let actual: Value = serde_json::from_slice(actual).map_err(|err| {
+            TesterError::assertion(format!(
+                "expected a JSON document, got {:?} ({err})",
+                String::from_utf8_lossy(actual)
+            ))
+        })?;
+
+        match json_difference(&expected, &actual, "$") {
+            None => Ok(()),
+            Some(difference) => {
+                Err(TesterError::assertion(format!("JSON mismatch at {difference}")).into())
+            }
+        }
+    }
+}
+
+/// Calls `f` up to `attempts` times (at least once), multiplying the delay by `factor` after each
+/// failed attempt.
+fn retry_with<T, F>(
+    attempts: usize,
+    mut delay: Duration,
+    factor: u32,
+    mut f: F,
+) -> Result<T, CaseError>
+where
+    F: FnMut() -> Result<T, CaseError>,
+{
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt >= attempts => return Err(err),
+            Err(_) => {
+                attempt += 1;
+                thread::sleep(delay);
+                delay = delay.saturating_mul(factor);
+            }
+        }
+    }
+}
+
+/// Returns the offset of the first byte at which `a` and `b` differ, if any.
+fn first_difference(a: &[u8], b: &[u8]) -> Option<usize> {
+    let common = a.iter().zip(b).position(|(x, y)| x != y);
+    common.or_else(|| (a.len() != b.len()).then(|| a.len().min(b.len())))
+}
+
+/// Renders the 16-byte rows of `expected` and `actual` around `offset` next to each other.
+fn hexdump_side_by_side(expected: &[u8], actual: &[u8], offset: usize) -> String {
+    const WIDTH: usize = HEXDUMP_WIDTH;
+    const ROWS: usize = 4;
+
+    let row = |bytes: &[u8], start: usize| -> String {
+        hexdump_row(bytes.get(start..bytes.len().min(start + WIDTH)).unwrap_or_default())
+    };
+
+    let first_row = offset / WIDTH * WIDTH;
+    let end = expected.len().max(actual.len()).min(first_row + ROWS * WIDTH);
+
+    let mut dump = format!("{:10}{:<67}   {}\n", "", "expected", "actual");
+    for start in (first_row..end).step_by(WIDTH) {
+        dump.push_str(&format!("{start:08x}  {}   {}\n", row(expected, start), row(actual, start)));
+    }
+
+    dump
+}
+
+/// Renders a line diff of `expected` and `actual` based on their longest common subsequence.
+fn line_diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // lcs[i][j] is the length of the longest common subsequence of expected[i..] and actual[j..].
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            diff.push_str(&format!("  {}\n", expected[i]));
+            i += 1;
+            j += 1;
+        } else if j == actual.len() || (i < expected.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push_str(&format!("- {}\n", expected[i]));
+            i += 1;
+        } else {
+            diff.push_str(&format!("+ {}\n", actual[j]));
+            j += 1;
+        }
+    }
+
+    diff
+}
+
+/// Describes the first difference between two JSON values as `<path>: <reason>`, descending
+/// into objects and arrays from `path`.
+fn json_difference(expected: &Value, actual: &Value, path: &str) -> Option<String> {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected {
+                let path = format!("{path}.{key}");
+                match actual.get(key) {
+                    Some(actual) => {
+                        if let Some(difference) = json_difference(value, actual, &path) {
+                            return Some(difference);
+                        }
+                    }
+                    None => {
+                        return Some(format!("{path}: expected {value}, but the key is missing"))
+                    }
+                }
+            }
+            let unexpected = actual.keys().find(|key| !expected.contains_key(*key))?;
+            Some(format!("{path}.{unexpected}: unexpected key"))
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            for (index, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                if let Some(difference) =
+                    json_difference(expected, actual, &format!("{path}[{index}]"))
+                {
+                    return Some(difference);
+                }
+            }
+            (expected.len() != actual.len()).then(|| {
+                format!("{path}: expected {} elements, got {}", expected.len(), actual.len())
+            })
+        }
+        (Value::Number(expected_number), Value::Number(actual_number)) => {
+            (!numbers_equal(expected_number, actual_number))
+                .then(|| format!("{path}: expected {expected}, got {actual}"))
+        }
+        _ => (expected != actual).then(|| format!("{path}: expected {expected}, got {actual}")),
+    }
+}
+
+/// Compares two JSON numbers by value: exactly if both are integers, as `f64` otherwise.
+fn numbers_equal(a: &Number, b: &Number) -> bool {
+    if a.is_f64() || b.is_f64() {
+        return a.as_f64() == b.as_f64();
+    }
+
+    match (a.as_i64(), b.as_i64()) {
+        (Some(a), Some(b)) => a == b,
+        // At least one is beyond `i64::MAX`, which only another `u64` can match.
+        _ => a.as_u64().is_some_and(|a| b.as_u64() == Some(a)),
+    }
 }
 
 impl fmt::Debug for Harness {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Harness").field("executable", &self.executable).finish()
+        f.debug_struct("Harness")
+            .field("executable", &self.executable)
+            .field("log_prefix", &self.log_prefix)
+            .finish()
     }
 }
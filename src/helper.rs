@@ -0,0 +1,51 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// A handle to a background helper thread started with
+/// [`Harness::spawn_helper`](crate::Harness::spawn_helper), such as a fake upstream server.
+///
+/// The helper receives a clone of the handle and should return once [`HelperHandle::is_stopped`]
+/// reports true. Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct HelperHandle {
+    stopped: Arc<AtomicBool>,
+    finished: Arc<AtomicBool>,
+}
+
+impl HelperHandle {
+    /// Asks the helper to stop. It is asked automatically on teardown.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns whether the helper was asked to stop.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    /// Returns whether the helper function has returned.
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    /// Records that the helper function has returned.
+    pub(crate) fn finish(&self) {
+        self.finished.store(true, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,37 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Number of bytes shown on each line of a hexdump.
+pub(crate) const HEXDUMP_WIDTH: usize = 16;
+
+/// Renders `bytes` in the classic `offset  hex-bytes  |ascii|` layout, 16 bytes per line, with
+/// bytes outside printable ASCII shown as `.` in the ASCII column.
+pub fn hexdump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(HEXDUMP_WIDTH)
+        .enumerate()
+        .map(|(index, chunk)| format!("{:08x}  {}\n", index * HEXDUMP_WIDTH, hexdump_row(chunk)))
+        .collect()
+}
+
+/// Renders up to 16 bytes as space-separated hex followed by their ASCII column, padded to the
+/// width of a full row so that rows line up.
+pub(crate) fn hexdump_row(chunk: &[u8]) -> String {
+    let hex: Vec<String> = chunk.iter().map(|b| format!("{b:02x}")).collect();
+    let ascii: String = chunk
+        .iter()
+        .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+        .collect();
+    format!("{:<47}  |{:<16}|", hex.join(" "), ascii)
+}
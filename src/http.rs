@@ -0,0 +1,81 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Result, TesterError};
+
+/// A parsed HTTP/1.x response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    /// The status code from the status line (e.g., `200`).
+    pub status: u16,
+    /// The headers in the order they were received.
+    pub headers: Vec<(String, String)>,
+    /// The response body.
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Returns the value of the first header named `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    /// Parses a complete response as read from a connection that the server closed.
+    ///
+    /// The body is cut to `Content-Length` when the header is present. Chunked transfer encoding
+    /// is not decoded.
+    pub(crate) fn parse(raw: &[u8]) -> Result<Self> {
+        let invalid = |msg: &str| TesterError::InvalidResponse(msg.to_string());
+
+        let head_end = raw
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .ok_or_else(|| invalid("missing end of headers"))?;
+        let head =
+            std::str::from_utf8(&raw[..head_end]).map_err(|_| invalid("headers are not UTF-8"))?;
+        let mut lines = head.split("\r\n");
+
+        let status_line = lines.next().unwrap_or_default();
+        let mut parts = status_line.splitn(3, ' ');
+        let version = parts.next().unwrap_or_default();
+        if !version.starts_with("HTTP/1.") {
+            return Err(invalid(&format!("malformed status line {status_line:?}")));
+        }
+        let status = parts
+            .next()
+            .and_then(|code| code.parse().ok())
+            .ok_or_else(|| invalid(&format!("malformed status line {status_line:?}")))?;
+
+        let headers = lines
+            .map(|line| {
+                let (name, value) = line
+                    .split_once(':')
+                    .ok_or_else(|| invalid(&format!("malformed header {line:?}")))?;
+                Ok((name.trim().to_string(), value.trim().to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut response = Self { status, headers, body: raw[head_end + 4..].to_vec() };
+        if let Some(length) = response.header("Content-Length") {
+            let length: usize =
+                length.parse().map_err(|_| invalid(&format!("bad Content-Length {length:?}")))?;
+            if response.body.len() < length {
+                return Err(invalid("body is shorter than Content-Length"));
+            }
+            response.body.truncate(length);
+        }
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    io::{self, Write},
+    process::ChildStdin,
+    time::Duration,
+};
+
+use crate::{Executable, Result, TesterError};
+
+/// The write half of an interactive session started with [`Executable::interact`].
+///
+/// It owns the process's stdin, so it can be moved to another thread and used while the
+/// [`StdoutReader`] waits for output. Dropping it closes stdin, signaling EOF to the process.
+/// Writes block while the process is not reading its input.
+#[derive(Debug)]
+pub struct StdinWriter {
+    stdin: ChildStdin,
+}
+
+impl StdinWriter {
+    pub(crate) fn new(stdin: ChildStdin) -> Self {
+        Self { stdin }
+    }
+
+    /// Writes a line to the process's stdin, appending a newline and flushing.
+    pub fn write_line(&mut self, line: &str) -> Result<()> {
+        self.stdin
+            .write_all(format!("{line}\n").as_bytes())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|err| TesterError::io_with_context(err, "failed to write to stdin"))
+    }
+}
+
+impl Write for StdinWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stdin.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdin.flush()
+    }
+}
+
+/// The read half of an interactive session started with [`Executable::interact`].
+///
+/// The executable's capture threads remain the only readers of the process's stdout pipe; this
+/// reader consumes what they have collected, exactly like [`Executable::read_until`]. Output read
+/// through it therefore still shows up in [`Executable::captured_stdout`] and in the output
+/// returned by [`Executable::wait`], and nothing is lost to a race between two readers.
+#[derive(Debug)]
+pub struct StdoutReader<'a> {
+    executable: &'a mut Executable,
+}
+
+impl<'a> StdoutReader<'a> {
+    pub(crate) fn new(executable: &'a mut Executable) -> Self {
+        Self { executable }
+    }
+
+    /// Reads the next line of stdout, including its trailing newline. Fails with
+    /// [`TesterError::WaitTimeout`] if no complete line arrives within `timeout`.
+    pub fn read_line(&mut self, timeout: Duration) -> Result<String> {
+        let line = self.executable.read_until(b"\n", timeout)?;
+        Ok(String::from_utf8_lossy(&line).into_owned())
+    }
+
+    /// Reads stdout until `needle` appears, like [`Executable::read_until`].
+    pub fn read_until(&mut self, needle: &[u8], timeout: Duration) -> Result<Vec<u8>> {
+        self.executable.read_until(needle, timeout)
+    }
+
+    /// Reads exactly `n` bytes from stdout, like [`Executable::read_exact_stdout`].
+    pub fn read_exact(&mut self, n: usize, timeout: Duration) -> Result<Vec<u8>> {
+        self.executable.read_exact_stdout(n, timeout)
+    }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod assert;
 mod case;
 mod cli;
 mod context;
@@ -19,16 +20,33 @@ mod definition;
 mod error;
 mod executable;
 mod harness;
+mod helper;
+mod hexdump;
+mod http;
+mod interact;
+mod logger;
+mod path;
+mod reporter;
 mod runner;
 mod tester;
+#[cfg(feature = "tls")]
+mod tls;
 
 // Re-exports
-pub use case::{Case, CaseError, Function};
-pub use cli::run;
-pub use context::Context;
-pub use definition::Definition;
+pub use assert::{assert_contains, assert_line_eq, normalize_newlines};
+pub use case::{Case, CaseError, Function, SkipPredicate};
+pub use cli::{color_enabled, init_logging, run, run_with_report};
+pub use context::{Context, OutputFormat};
+pub use definition::{Definition, DefinitionBuilder};
 pub use error::{Result, TesterError};
-pub use executable::Executable;
+pub use executable::{Executable, ExitReason};
 pub use harness::Harness;
-pub use runner::{Runner, Step};
+pub use helper::HelperHandle;
+pub use hexdump::hexdump;
+pub use http::HttpResponse;
+pub use interact::{StdinWriter, StdoutReader};
+pub use logger::Logger;
+pub use path::{join_within, resolve_within};
+pub use reporter::{Reporter, TracingReporter};
+pub use runner::{RunReport, RunSummary, Runner, Step, StepOutcome};
 pub use tester::Tester;
@@ -0,0 +1,54 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use tracing::{debug, error, info, warn};
+
+/// Emits log lines tagged with the log prefix of the current test step (e.g., `"stage-1"`).
+#[derive(Debug, Clone)]
+pub struct Logger {
+    prefix: String,
+}
+
+impl Logger {
+    /// Creates a new `Logger` with the given prefix.
+    pub fn new<S: Into<String>>(prefix: S) -> Self {
+        Self { prefix: prefix.into() }
+    }
+
+    /// Returns the prefix attached to every log line.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Logs a message at the info level.
+    pub fn infof(&self, message: impl fmt::Display) {
+        info!("[{}] {}", self.prefix, message);
+    }
+
+    /// Logs a message at the debug level.
+    pub fn debugf(&self, message: impl fmt::Display) {
+        debug!("[{}] {}", self.prefix, message);
+    }
+
+    /// Logs a message at the warn level.
+    pub fn warnf(&self, message: impl fmt::Display) {
+        warn!("[{}] {}", self.prefix, message);
+    }
+
+    /// Logs a message at the error level.
+    pub fn errorf(&self, message: impl fmt::Display) {
+        error!("[{}] {}", self.prefix, message);
+    }
+}
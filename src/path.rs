@@ -0,0 +1,78 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::{Result, TesterError};
+
+/// Joins `relative` onto `base`, checking lexically that the result stays inside `base`.
+///
+/// Absolute paths and `..` components that climb out of `base` are rejected with
+/// [`TesterError::InvalidPath`], whether or not they exist. Symlinks are not followed, so a link
+/// inside `base` is accepted wherever it points. Returns the normalized path under the canonical
+/// form of `base`.
+pub fn join_within(base: &Path, relative: &str) -> Result<PathBuf> {
+    let normalized = normalize_within(base, relative)?;
+
+    Ok(canonicalize(base)?.join(normalized))
+}
+
+/// Resolves `relative` against `base` and checks that the result stays inside `base`.
+///
+/// The path is first checked lexically like [`join_within`]. It is then canonicalized, which
+/// also rejects symlinks pointing outside `base`. Returns the canonical path, or
+/// [`TesterError::InvalidPath`] if it escapes `base`.
+pub fn resolve_within(base: &Path, relative: &str) -> Result<PathBuf> {
+    let normalized = normalize_within(base, relative)?;
+    let canonical_base = canonicalize(base)?;
+    let canonical_path = canonicalize(&canonical_base.join(normalized))?;
+
+    if !canonical_path.starts_with(&canonical_base) {
+        return Err(escapes(base, relative));
+    }
+
+    Ok(canonical_path)
+}
+
+/// Removes `.` and `..` components from `relative`, failing if it climbs out of `base` or is
+/// absolute.
+fn normalize_within(base: &Path, relative: &str) -> Result<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in Path::new(relative).components() {
+        match component {
+            Component::Normal(name) => normalized.push(name),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return Err(escapes(base, relative));
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return Err(escapes(base, relative)),
+        }
+    }
+
+    Ok(normalized)
+}
+
+/// Canonicalizes `path`, naming it in the error.
+fn canonicalize(path: &Path) -> Result<PathBuf> {
+    path.canonicalize().map_err(|err| {
+        TesterError::io_with_context(err, &format!("failed to resolve {}", path.display()))
+    })
+}
+
+/// The error for `relative` escaping `base`.
+fn escapes(base: &Path, relative: &str) -> TesterError {
+    TesterError::InvalidPath(format!("{relative} is outside of {}", base.display()))
+}
@@ -0,0 +1,121 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{error, info, warn};
+
+use crate::{RunSummary, Step, StepOutcome};
+
+/// Receives the events of a run as [`Runner`](crate::Runner) executes its steps, e.g. to drive a
+/// progress bar or a web dashboard instead of scraping logs.
+pub trait Reporter: Send + Sync {
+    /// Called before a step is executed or skipped.
+    fn on_step_start(&self, step: &Step<'_>);
+
+    /// Called when a step has passed.
+    fn on_step_pass(&self, outcome: &StepOutcome);
+
+    /// Called when a step has failed with the error `err`.
+    fn on_step_fail(&self, outcome: &StepOutcome, err: &str);
+
+    /// Called when a step was skipped, because its skip condition held or a test case it depends
+    /// on failed.
+    fn on_step_skip(&self, outcome: &StepOutcome, reason: &str);
+
+    /// Called once every step that was going to run has been reported.
+    fn on_run_complete(&self, outcomes: &[StepOutcome]);
+}
+
+/// The default [`Reporter`], which logs each step through `tracing`. Passed steps are logged at
+/// the info level, failed ones at the error level and skipped ones at the warn level, so that a
+/// subscriber that colors levels shows them in green, red and yellow.
+#[derive(Debug, Default)]
+pub struct TracingReporter {
+    is_quiet: bool, // Used for anti-cheat tests, where only critical logs are emitted.
+    is_debug: bool, // Whether the author asked for more details on failures.
+    has_started: AtomicBool, // Whether a step was started, to separate steps by a blank line.
+    text_to_stderr: bool, // Whether blank lines go to stderr, e.g. when stdout carries JSON.
+}
+
+impl TracingReporter {
+    /// Creates a new `TracingReporter`. In quiet mode, only failures are logged.
+    pub fn new(is_quiet: bool, is_debug: bool) -> Self {
+        Self { is_quiet, is_debug, has_started: AtomicBool::new(false), text_to_stderr: false }
+    }
+
+    /// Writes the blank lines that separate steps to stderr instead of stdout, like the logs of
+    /// a subscriber installed for the JSON output format.
+    pub fn with_text_to_stderr(mut self, enabled: bool) -> Self {
+        self.text_to_stderr = enabled;
+        self
+    }
+
+    /// Writes a blank line to separate parts of the output.
+    fn blank_line(&self) {
+        if self.text_to_stderr {
+            eprintln!();
+        } else {
+            println!();
+        }
+    }
+}
+
+impl Reporter for TracingReporter {
+    fn on_step_start(&self, step: &Step<'_>) {
+        if self.has_started.swap(true, Ordering::Relaxed) {
+            self.blank_line();
+        }
+
+        if !self.is_quiet {
+            info!("Running tests for {}", step.title);
+        }
+    }
+
+    fn on_step_pass(&self, outcome: &StepOutcome) {
+        if !self.is_quiet {
+            info!("Test passed in {} ms.", outcome.duration.as_millis());
+        }
+    }
+
+    fn on_step_fail(&self, outcome: &StepOutcome, err: &str) {
+        error!("{}", err);
+        if let Some(description) = &outcome.description {
+            error!("Stage verifies: {description}");
+        }
+
+        let millis = outcome.duration.as_millis();
+        if self.is_debug {
+            error!("Test failed after {millis} ms");
+        } else {
+            error!(
+                "Test failed after {millis} ms (try setting 'debug: true' in your codecrafters.yml to see more details)"
+            );
+        }
+    }
+
+    fn on_step_skip(&self, _outcome: &StepOutcome, reason: &str) {
+        if !self.is_quiet {
+            warn!("Test {reason}.");
+        }
+    }
+
+    fn on_run_complete(&self, outcomes: &[StepOutcome]) {
+        if !self.is_quiet {
+            if self.has_started.load(Ordering::Relaxed) {
+                self.blank_line();
+            }
+            info!("{}", RunSummary::from_outcomes(outcomes));
+        }
+    }
+}
@@ -12,15 +12,48 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{sync::mpsc, thread};
-use tracing::{Level, error, info, span};
+use serde::{Serialize, Serializer};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, IsTerminal, Write},
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::{Dispatch, Level, Span, Subscriber, dispatcher, level_filters::LevelFilter, span};
 
-use crate::{Case, Executable, Harness};
+use crate::{
+    Case, CaseError, Executable, Harness, Reporter, TracingReporter, cli::color_enabled,
+    executable::OutputSink, harness::SeedCell,
+};
+
+/// How long each teardown function of a step may run before it is abandoned, unless set with
+/// [`Runner::with_teardown_timeout`].
+const DEFAULT_TEARDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often a cancellable run checks its cancellation flag while a step runs.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 /// Represents a test runner that executes a sequence of test steps.
 pub struct Runner<'a> {
     steps: Vec<Step<'a>>,
-    _is_quiet: bool, // Used for anti-cheat tests, where only critical logs are emitted.
+    is_quiet: bool, // Used for anti-cheat tests, where only critical logs are emitted.
+    timeout: Option<Duration>, // Upper bound applied to every case's own timeout.
+    total_timeout: Option<Duration>, // Upper bound on the duration of a whole sequential run.
+    deadline: Option<Instant>, // End of the total timeout, if shared with work outside the run.
+    teardown_timeout: Duration, // Upper bound on each teardown function of a step.
+    repository_dir: Option<PathBuf>, // Handed to every harness for submission file access.
+    env: Arc<HashMap<String, String>>, // Handed to every harness for configuration lookups.
+    buffer_output: bool, // Write each step's logs and program output as one block.
+    reporter: Option<Arc<dyn Reporter>>, // Receives the run's events instead of the logs.
+    seed: SeedCell, // Random seed shared by the harnesses of every step.
+    text_to_stderr: bool, // Write text output to stderr, keeping stdout for results.
 }
 
 /// Represents a single step in a test runner.
@@ -33,79 +66,672 @@ pub struct Step<'a> {
     pub title: &'a str,
 }
 
+/// The outcome of executing a single test step.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepOutcome {
+    /// The slug of the executed test case.
+    pub slug: String,
+    /// The human-readable title of the step.
+    pub title: String,
+    /// Whether the step passed.
+    pub passed: bool,
+    /// Whether the step was skipped. A step skipped by its skip condition counts as passed, while
+    /// one skipped because a test case it depends on failed has not passed.
+    pub skipped: bool,
+    /// Wall-clock time spent on the step, including teardown.
+    #[serde(rename = "duration_ms", serialize_with = "serialize_millis")]
+    pub duration: Duration,
+    /// The error message reported by a failing step, or the reason a step was skipped.
+    pub error: Option<String>,
+    /// What the step's test case verifies, if it has a description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Per-step timings of a run, in step order.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RunReport {
+    /// The outcomes of the executed steps.
+    pub steps: Vec<StepOutcome>,
+}
+
+impl RunReport {
+    /// Returns whether every executed step passed.
+    pub fn passed(&self) -> bool {
+        self.steps.iter().all(|step| step.passed)
+    }
+
+    /// Counts the executed steps by how they ended.
+    pub fn summary(&self) -> RunSummary {
+        RunSummary::from_outcomes(&self.steps)
+    }
+
+    /// Returns the sum of the step durations.
+    pub fn total_duration(&self) -> Duration {
+        self.steps.iter().map(|step| step.duration).sum()
+    }
+
+    /// Renders the step timings as a plain-text table, one row per step plus a total.
+    pub fn timing_table(&self) -> String {
+        let width = self.steps.iter().map(|step| step.slug.len()).max().unwrap_or(0).max(5);
+
+        let mut table =
+            format!("{:<width$}  {:<7}  {:>10}  {}\n", "slug", "status", "time", "title");
+        for step in &self.steps {
+            let status = match (step.passed, step.skipped) {
+                (_, true) => "skipped",
+                (true, false) => "passed",
+                (false, false) => "failed",
+            };
+            let millis = format!("{} ms", step.duration.as_millis());
+            table.push_str(&format!(
+                "{:<width$}  {:<7}  {:>10}  {}\n",
+                step.slug, status, millis, step.title
+            ));
+        }
+        let total = format!("{} ms", self.total_duration().as_millis());
+        table.push_str(&format!("{:<width$}  {:<7}  {:>10}\n", "total", "", total));
+        table
+    }
+}
+
+/// Counts of how the steps of a run ended, e.g. for a final "5 of 5 steps passed" line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RunSummary {
+    /// The number of steps with an outcome.
+    pub total: usize,
+    /// The number of steps that ran and passed.
+    pub passed: usize,
+    /// The number of steps that ran and failed.
+    pub failed: usize,
+    /// The number of skipped steps, whether by their skip condition or a failed dependency.
+    pub skipped: usize,
+}
+
+impl RunSummary {
+    /// Counts `outcomes` by how each step ended.
+    pub fn from_outcomes(outcomes: &[StepOutcome]) -> Self {
+        let mut summary = Self { total: outcomes.len(), ..Self::default() };
+        for outcome in outcomes {
+            match (outcome.passed, outcome.skipped) {
+                (_, true) => summary.skipped += 1,
+                (true, false) => summary.passed += 1,
+                (false, false) => summary.failed += 1,
+            }
+        }
+        summary
+    }
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} steps passed ({} failed, {} skipped)",
+            self.passed, self.total, self.failed, self.skipped
+        )
+    }
+}
+
+/// Appends log lines to a step's output buffer.
+struct SinkWriter(OutputSink);
+
+impl Write for SinkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serializes a duration as a whole number of milliseconds.
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u128(duration.as_millis())
+}
+
 impl<'a> Runner<'a> {
     /// Creates a new `Runner` with the given steps.
     pub fn new(steps: Vec<Step<'a>>) -> Self {
-        Self { steps, _is_quiet: false }
+        Self {
+            steps,
+            is_quiet: false,
+            timeout: None,
+            total_timeout: None,
+            deadline: None,
+            teardown_timeout: DEFAULT_TEARDOWN_TIMEOUT,
+            repository_dir: None,
+            env: Arc::default(),
+            buffer_output: false,
+            reporter: None,
+            seed: Arc::default(),
+            text_to_stderr: false,
+        }
     }
 
     /// Creates a new `Runner` with quiet mode enabled.
     pub fn new_quiet(steps: Vec<Step<'a>>) -> Self {
-        Self { steps, _is_quiet: true }
+        Self {
+            steps,
+            is_quiet: true,
+            timeout: None,
+            total_timeout: None,
+            deadline: None,
+            teardown_timeout: DEFAULT_TEARDOWN_TIMEOUT,
+            repository_dir: None,
+            env: Arc::default(),
+            buffer_output: false,
+            reporter: None,
+            seed: Arc::default(),
+            text_to_stderr: false,
+        }
+    }
+
+    /// Caps the timeout of every step at `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Bounds the wall-clock time of a whole run by [`Runner::run`] or [`Runner::run_all`].
+    ///
+    /// Once `budget` is used up, the step in progress is interrupted and fails, and the run
+    /// stops there, so a suite of slow steps cannot hold a shared CI runner indefinitely.
+    pub fn with_total_timeout(mut self, budget: Duration) -> Self {
+        self.total_timeout = Some(budget);
+        self
+    }
+
+    /// Sets how long each teardown function of a step may run before it is abandoned with a
+    /// warning (10 seconds by default).
+    pub fn with_teardown_timeout(mut self, timeout: Duration) -> Self {
+        self.teardown_timeout = timeout;
+        self
     }
 
-    /// Executes all test steps in sequence.
-    pub fn run(&self, is_debug: bool, executable: &Executable) -> bool {
-        for (index, step) in self.steps.iter().enumerate() {
-            if index != 0 {
-                println!();
+    /// Ends runs at `deadline` instead of once the total timeout has elapsed from their start, so
+    /// that the budget also covers work before the run, such as a build or another runner.
+    pub(crate) fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the submission directory that each step's harness resolves file paths against.
+    pub fn with_repository_dir<P: Into<PathBuf>>(mut self, repository_dir: P) -> Self {
+        self.repository_dir = Some(repository_dir.into());
+        self
+    }
+
+    /// Sets the environment variables exposed to each step's harness.
+    pub fn with_env(mut self, env: Arc<HashMap<String, String>>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Collects the logs of each step, together with the output of its programs that are
+    /// forwarded with [`Executable::with_tee`], and writes them to stdout as one block between
+    /// `=== begin <slug> ===` and `=== end <slug> ===` markers. Log levels are colored under the
+    /// same conditions as [`init_logging`](crate::init_logging), judged by the runner's
+    /// environment. [`Runner::run_parallel`] always collects each step's output, but only adds
+    /// the markers when this is enabled.
+    pub fn with_buffered_output(mut self, enabled: bool) -> Self {
+        self.buffer_output = enabled;
+        self
+    }
+
+    /// Shares the random seed of [`Harness::rng`] with another runner of the same run.
+    pub(crate) fn with_seed(mut self, seed: SeedCell) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Writes the runner's own text output, i.e. the blank lines between steps and the blocks of
+    /// collected step output, to stderr instead of stdout, so that stdout can carry
+    /// machine-readable results.
+    pub fn with_text_to_stderr(mut self, enabled: bool) -> Self {
+        self.text_to_stderr = enabled;
+        self
+    }
+
+    /// Sends the events of each run to `reporter` instead of logging them with a
+    /// [`TracingReporter`].
+    pub fn with_reporter(mut self, reporter: Arc<dyn Reporter>) -> Self {
+        self.reporter = Some(reporter);
+        self
+    }
+
+    /// Executes all test steps in sequence, stopping at the first failure.
+    /// Returns the outcomes of the steps that were executed.
+    pub fn run(&self, is_debug: bool, executable: &Executable) -> Vec<StepOutcome> {
+        self.run_steps(is_debug, executable, true, None)
+    }
+
+    /// Executes the test steps like [`Runner::run`] until `cancel` is set, e.g. by a service
+    /// whose user disconnected. The flag is checked between steps and while a step runs: the
+    /// step in progress then fails, the processes it started are killed, and the run returns
+    /// without starting the remaining steps.
+    pub fn run_cancellable(
+        &self,
+        is_debug: bool,
+        executable: &Executable,
+        cancel: Arc<AtomicBool>,
+    ) -> Vec<StepOutcome> {
+        self.run_steps(is_debug, executable, true, Some(cancel))
+    }
+
+    /// Executes all test steps like [`Runner::run`] and returns a report of how long each step
+    /// took, from just before its setup until after its teardown.
+    pub fn run_reporting(&self, is_debug: bool, executable: &Executable) -> RunReport {
+        RunReport { steps: self.run(is_debug, executable) }
+    }
+
+    /// Executes every test step in sequence regardless of failures.
+    /// Returns the outcomes of all steps.
+    pub fn run_all(&self, is_debug: bool, executable: &Executable) -> Vec<StepOutcome> {
+        self.run_steps(is_debug, executable, false, None)
+    }
+
+    /// Executes the test steps in sequence, optionally stopping at the first failure, until
+    /// `cancel`, if any, is set.
+    fn run_steps(
+        &self,
+        is_debug: bool,
+        executable: &Executable,
+        stop_on_failure: bool,
+        cancel: Option<Arc<AtomicBool>>,
+    ) -> Vec<StepOutcome> {
+        let reporter = self.reporter(is_debug);
+        let mut outcomes = Vec::with_capacity(self.steps.len());
+        let limits = RunLimits { deadline: self.run_deadline(), cancel };
+
+        for step in &self.steps {
+            if limits.is_cancelled() {
+                break;
+            }
+
+            let outcome = if self.buffer_output {
+                self.run_step_buffered(step, &*reporter, executable, &outcomes, &limits)
+            } else {
+                self.run_step(step, &*reporter, executable.clone(), &outcomes, &limits)
+            };
+
+            let passed = outcome.passed;
+            outcomes.push(outcome);
+            if !passed && (stop_on_failure || limits.is_out_of_time()) {
+                break;
             }
+        }
+
+        reporter.on_run_complete(&outcomes);
+        outcomes
+    }
+
+    /// Returns the reporter set with [`Runner::with_reporter`], or a [`TracingReporter`].
+    fn reporter(&self, is_debug: bool) -> Arc<dyn Reporter> {
+        match &self.reporter {
+            Some(reporter) => reporter.clone(),
+            None => Arc::new(
+                TracingReporter::new(self.is_quiet, is_debug)
+                    .with_text_to_stderr(self.text_to_stderr),
+            ),
+        }
+    }
+
+    /// Executes a single step, unless a dependency failed among the `previous` outcomes, and
+    /// reports it. The step is cut short once the run's `limits` are reached.
+    fn run_step(
+        &self,
+        step: &Step<'_>,
+        reporter: &dyn Reporter,
+        executable: Executable,
+        previous: &[StepOutcome],
+        limits: &RunLimits,
+    ) -> StepOutcome {
+        let span = step_span(step);
+        let _enter = span.enter();
+
+        reporter.on_step_start(step);
+        let outcome = self.execute_or_skip(step, executable, previous, limits);
+        report_outcome(reporter, &outcome);
+        outcome
+    }
 
-            let span =
-                span!(Level::INFO, "test_step", log_prefix = step.log_prefix, title = step.title);
-            let _enter = span.enter();
+    /// Executes a single step like [`Runner::run_step`], holding back its logs and forwarded
+    /// program output until it has finished so they can be written out in one piece.
+    fn run_step_buffered(
+        &self,
+        step: &Step<'_>,
+        reporter: &dyn Reporter,
+        executable: &Executable,
+        previous: &[StepOutcome],
+        limits: &RunLimits,
+    ) -> StepOutcome {
+        let sink = OutputSink::default();
+        let executable = executable.clone().with_tee_sink(sink.clone());
+        let outcome = tracing::subscriber::with_default(self.sink_subscriber(&sink), || {
+            self.run_step(step, reporter, executable, previous, limits)
+        });
 
-            info!("Running tests for {}", step.title);
+        self.write_step_output(step, &sink.lock().unwrap());
 
-            let harness = Harness::new(executable.clone());
-            let (tx, rx) = mpsc::channel();
+        outcome
+    }
 
-            let case_function = step.case.function.clone();
-            let harness_clone = harness.clone();
+    /// Returns a subscriber that writes log lines to `sink`, colored like the global one.
+    fn sink_subscriber(&self, sink: &OutputSink) -> impl Subscriber + Send + Sync + use<> {
+        let writer = sink.clone();
+        tracing_subscriber::fmt()
+            .with_writer(move || SinkWriter(writer.clone()))
+            .with_max_level(LevelFilter::current())
+            .with_ansi(color_enabled(&self.env, self.output_is_terminal()))
+            .finish()
+    }
+
+    /// Returns whether the stream that step output is written to is a terminal.
+    fn output_is_terminal(&self) -> bool {
+        if self.text_to_stderr { io::stderr().is_terminal() } else { io::stdout().is_terminal() }
+    }
 
-            thread::spawn(move || {
-                let result = case_function(&harness_clone);
-                tx.send(result).unwrap();
+    /// Writes the collected output of a step to stdout (or stderr) in one piece, between `=== begin
+    /// ===` and `=== end ===` markers if buffered output is enabled.
+    fn write_step_output(&self, step: &Step<'_>, output: &[u8]) {
+        let slug = &step.case.slug;
+        let mut block = Vec::new();
+        if self.buffer_output {
+            block.extend_from_slice(format!("=== begin {slug} ===\n").as_bytes());
+        }
+        block.extend_from_slice(output);
+        if self.buffer_output {
+            block.extend_from_slice(format!("=== end {slug} ===\n").as_bytes());
+        }
+        let _ = if self.text_to_stderr {
+            io::stderr().lock().write_all(&block)
+        } else {
+            io::stdout().lock().write_all(&block)
+        };
+    }
+
+    /// Executes all test steps concurrently, running at most `max_concurrency` steps at a time.
+    ///
+    /// Every step runs to completion regardless of failures, unless the run's total timeout is
+    /// used up. The logs of each step, together with the output of its programs that are
+    /// forwarded with [`Executable::with_tee`], are collected while it runs and written to stdout
+    /// in one piece, in step order, once its batch has finished. A step is only skipped for a
+    /// failed dependency that ran in an earlier batch.
+    pub fn run_parallel(
+        &self,
+        is_debug: bool,
+        executable: &Executable,
+        max_concurrency: usize,
+    ) -> Vec<StepOutcome> {
+        let reporter = self.reporter(is_debug);
+        let mut outcomes = Vec::with_capacity(self.steps.len());
+        let limits = &RunLimits { deadline: self.run_deadline(), cancel: None };
+
+        for batch in self.steps.chunks(max_concurrency.max(1)) {
+            let previous = &outcomes;
+            let batch_results: Vec<_> = thread::scope(|scope| {
+                let handles: Vec<_> = batch
+                    .iter()
+                    .map(|step| {
+                        let sink = OutputSink::default();
+                        let executable = executable.clone().with_tee_sink(sink.clone());
+                        scope.spawn(move || {
+                            let subscriber = self.sink_subscriber(&sink);
+                            let outcome = tracing::subscriber::with_default(subscriber, || {
+                                let span = step_span(step);
+                                let _enter = span.enter();
+                                self.execute_or_skip(step, executable, previous, limits)
+                            });
+                            (outcome, sink)
+                        })
+                    })
+                    .collect();
+
+                handles.into_iter().map(|handle| handle.join().unwrap()).collect()
             });
 
-            let timeout = step.case.default_timeout();
-
-            let result = match rx.recv_timeout(timeout) {
-                Ok(Ok(())) => {
-                    info!("Test passed.");
-                    true
-                }
-                Ok(Err(err)) => {
-                    self.report_test_error(&err, is_debug);
-                    false
-                }
-                Err(_) => {
-                    let err = format!("timed out, test exceeded {} seconds", timeout.as_secs());
-                    self.report_test_error(&err, is_debug);
-                    false
-                }
+            for (step, (outcome, sink)) in batch.iter().zip(batch_results) {
+                // Bracket the step's output with its reports, as if it had run on its own.
+                let block = OutputSink::default();
+                tracing::subscriber::with_default(self.sink_subscriber(&block), || {
+                    let span = step_span(step);
+                    let _enter = span.enter();
+                    reporter.on_step_start(step);
+                    block.lock().unwrap().extend_from_slice(&sink.lock().unwrap());
+                    report_outcome(&*reporter, &outcome);
+                });
+                self.write_step_output(step, &block.lock().unwrap());
+                outcomes.push(outcome);
+            }
+        }
+
+        reporter.on_run_complete(&outcomes);
+        outcomes
+    }
+
+    /// Executes the step, or skips it if a test case it depends on failed among the `previous`
+    /// outcomes or its skip condition holds. Dependencies that did not run are ignored. A step
+    /// that would start after the run's deadline fails without running.
+    fn execute_or_skip(
+        &self,
+        step: &Step<'_>,
+        executable: Executable,
+        previous: &[StepOutcome],
+        limits: &RunLimits,
+    ) -> StepOutcome {
+        let failed_dependency =
+            step.case.depends_on.iter().find(|slug| {
+                previous.iter().any(|outcome| outcome.slug == **slug && !outcome.passed)
+            });
+
+        let skipped = |passed, reason: String| StepOutcome {
+            slug: step.case.slug.clone(),
+            title: step.title.to_string(),
+            passed,
+            skipped: true,
+            duration: Duration::ZERO,
+            error: Some(reason),
+            description: step.case.description.clone(),
+        };
+
+        if let Some(slug) = failed_dependency {
+            return skipped(false, format!("skipped due to failed dependency {slug}"));
+        }
+        if step.case.skip_if.as_ref().is_some_and(|predicate| predicate(&self.env)) {
+            return skipped(true, "skipped because its skip condition holds".to_string());
+        }
+        if limits.is_out_of_time() {
+            return StepOutcome {
+                slug: step.case.slug.clone(),
+                title: step.title.to_string(),
+                passed: false,
+                skipped: false,
+                duration: Duration::ZERO,
+                error: Some(self.out_of_time_message("not run")),
+                description: step.case.description.clone(),
             };
+        }
+
+        self.execute(step, executable, limits)
+    }
+
+    /// Runs the step's setup and case functions against a fresh harness, enforcing the step
+    /// timeout, and runs the registered teardown functions afterwards. A failing step is
+    /// attempted again, up to the case's number of retries.
+    fn execute(
+        &self,
+        step: &Step<'_>,
+        mut executable: Executable,
+        limits: &RunLimits,
+    ) -> StepOutcome {
+        let start = Instant::now();
+        for (key, value) in &step.case.env {
+            executable.env(key, value);
+        }
 
-            harness.run_teardown_funcs();
+        let mut harness = Harness::new(executable)
+            .with_log_prefix(step.log_prefix)
+            .with_env(self.env.clone())
+            .with_seed(self.seed.clone());
+        if let Some(repository_dir) = &self.repository_dir {
+            harness = harness.with_repository_dir(repository_dir);
+        }
+        if limits.cancel.is_some() {
+            // Cancellation has to be able to kill every process the step starts.
+            harness = harness.tracking_all_processes();
+        }
 
-            if !result {
-                return false;
+        let mut result = self.execute_attempt(step, &harness, limits);
+        for _ in 0..step.case.retries {
+            if result.is_ok() {
+                break;
             }
+            result = self.execute_attempt(step, &harness, limits);
         }
 
-        true
+        StepOutcome {
+            slug: step.case.slug.clone(),
+            title: step.title.to_string(),
+            passed: result.is_ok(),
+            skipped: false,
+            duration: start.elapsed(),
+            error: result.err().map(|err| err.to_string()),
+            description: step.case.description.clone(),
+        }
     }
 
-    /// Reports a test error with appropriate logging.
-    fn report_test_error(&self, err: &impl std::fmt::Display, is_debug: bool) {
-        error!("{}", err);
+    /// Runs the setup and case functions once, followed by the registered teardown functions.
+    fn execute_attempt(
+        &self,
+        step: &Step<'_>,
+        harness: &Harness,
+        limits: &RunLimits,
+    ) -> Result<(), CaseError> {
+        let setup_result: Result<(), CaseError> = match &step.case.setup {
+            Some(setup) => setup(harness).map_err(|err| format!("setup failed: {err}").into()),
+            None => Ok(()),
+        };
+        let result = setup_result.and_then(|()| self.execute_case(step, harness, limits));
 
-        if is_debug {
-            error!("Test failed");
-        } else {
-            error!(
-                "Test failed (try setting 'debug: true' in your codecrafters.yml to see more details)"
-            );
+        // Teardown time counts against the run's budget too.
+        let teardown_timeout = limits.deadline.map_or(self.teardown_timeout, |deadline| {
+            self.teardown_timeout.min(deadline.saturating_duration_since(Instant::now()))
+        });
+        harness.run_teardown_funcs_with_timeout(teardown_timeout);
+
+        result
+    }
+
+    /// Runs the case function on its own thread, enforcing the step timeout and the run's
+    /// deadline, whichever comes first. If the run is cancelled meanwhile, the processes the
+    /// step started are killed and the step fails.
+    fn execute_case(
+        &self,
+        step: &Step<'_>,
+        harness: &Harness,
+        limits: &RunLimits,
+    ) -> Result<(), CaseError> {
+        let (tx, rx) = mpsc::channel();
+
+        let case_function = step.case.function.clone();
+        let harness_clone = harness.clone();
+        // Keep logging to the step's subscriber, which may be collecting its output.
+        let dispatch = dispatcher::get_default(Dispatch::clone);
+
+        let start = Instant::now();
+        thread::spawn(move || {
+            let result = dispatcher::with_default(&dispatch, || case_function(&harness_clone));
+            tx.send(result).unwrap();
+        });
+
+        let timeout = self.step_timeout(step);
+        let remaining = limits
+            .deadline
+            .map(|deadline| deadline.saturating_duration_since(start))
+            .filter(|remaining| *remaining < timeout);
+        let wait = remaining.unwrap_or(timeout);
+
+        let received = loop {
+            if limits.is_cancelled() {
+                harness.kill_processes();
+                return Err(format!("cancelled after {} ms", start.elapsed().as_millis()).into());
+            }
+
+            let left = wait.saturating_sub(start.elapsed());
+            let poll = if limits.cancel.is_some() { left.min(CANCEL_POLL_INTERVAL) } else { left };
+            match rx.recv_timeout(poll) {
+                Err(RecvTimeoutError::Timeout) if start.elapsed() < wait => continue,
+                received => break received,
+            }
+        };
+
+        match received {
+            Ok(result) => result,
+            Err(_) if remaining.is_some() => {
+                let interrupted = format!("interrupted after {} ms", start.elapsed().as_millis());
+                Err(self.out_of_time_message(&interrupted).into())
+            }
+            Err(_) => Err(format!(
+                "timed out after {} ms, test exceeded {} seconds",
+                start.elapsed().as_millis(),
+                timeout.as_secs()
+            )
+            .into()),
         }
     }
+
+    /// Explains that a step `what` (e.g. "not run") because the run used up its total timeout.
+    fn out_of_time_message(&self, what: &str) -> String {
+        let budget = self.total_timeout.unwrap_or_default();
+        format!("{what}, the run exceeded its total timeout of {budget:?}")
+    }
+
+    /// Returns when a run starting now has to end, if its duration is bounded.
+    fn run_deadline(&self) -> Option<Instant> {
+        self.deadline.or_else(|| self.total_timeout.map(|budget| Instant::now() + budget))
+    }
+
+    /// Returns the case timeout, capped by the runner timeout if one is set.
+    fn step_timeout(&self, step: &Step<'_>) -> Duration {
+        let timeout = step.case.default_timeout();
+        self.timeout.map_or(timeout, |cap| timeout.min(cap))
+    }
+}
+
+/// Conditions that cut a sequential run short.
+#[derive(Default)]
+struct RunLimits {
+    /// When the run's total timeout is used up.
+    deadline: Option<Instant>,
+    /// Set from outside the runner to cancel the run.
+    cancel: Option<Arc<AtomicBool>>,
+}
+
+impl RunLimits {
+    /// Checks whether the run has used up its total timeout.
+    fn is_out_of_time(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Checks whether the run has been cancelled.
+    fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::SeqCst))
+    }
+}
+
+/// Returns the span that the logs of a step are recorded in.
+fn step_span(step: &Step<'_>) -> Span {
+    span!(Level::INFO, "test_step", log_prefix = step.log_prefix, title = step.title)
+}
+
+/// Passes the outcome of a step to the matching reporter callback.
+fn report_outcome(reporter: &dyn Reporter, outcome: &StepOutcome) {
+    match &outcome.error {
+        None => reporter.on_step_pass(outcome),
+        Some(reason) if outcome.skipped => reporter.on_step_skip(outcome, reason),
+        Some(err) => reporter.on_step_fail(outcome, err),
+    }
 }
@@ -11,10 +11,23 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::warn;
 
-use std::collections::HashMap;
+use crate::{
+    Case, Context, Definition, Executable, OutputFormat, Result, RunReport, Runner, Step,
+    TesterError, executable, harness::SeedCell,
+};
 
-use crate::{Context, Definition, Executable, Result, Runner, Step};
+/// How long the build command may run, unless less of the total timeout remains.
+const BUILD_TIMEOUT: Duration = Duration::from_secs(300);
 
 /// Manages the execution environment & runner for test cases.
 pub struct Tester {
@@ -23,6 +36,9 @@ pub struct Tester {
 
     /// Test definition with all test cases and configs
     definition: Definition,
+
+    /// Random seed shared by the harnesses of the regular and anti-cheat stages
+    seed: SeedCell,
 }
 
 impl Tester {
@@ -30,12 +46,116 @@ impl Tester {
     pub fn new(env: HashMap<String, String>, definition: Definition) -> Result<Self> {
         let context = Context::from_env(env, &definition)?;
 
-        Ok(Self { context, definition })
+        Ok(Self { context, definition, seed: SeedCell::default() })
     }
 
-    /// Runs all stages up to the current stage. Returns true if all stages pass.
+    /// Runs all stages up to the current stage, followed by the anti-cheat stages unless they
+    /// are skipped. Returns true if all stages pass.
+    ///
+    /// When the JSON output format is selected, the outcomes of all executed steps are printed to
+    /// stdout as a JSON array once the run is over. When timing is enabled, a table of per-step
+    /// durations is printed as well.
     pub fn run(&self) -> Result<bool> {
-        Ok(self.build_runner().run(self.context.is_debug, &self.get_executable()?))
+        Ok(self.run_report()?.passed())
+    }
+
+    /// Runs the stages like [`Tester::run`] and returns the outcome of every executed step.
+    ///
+    /// If the definition has a build command, it is run first, and a failing build is returned
+    /// as an error before any stage runs. The build may take up to five minutes. The total
+    /// timeout, if set, covers the build as well as the regular and anti-cheat stages.
+    pub fn run_report(&self) -> Result<RunReport> {
+        let deadline = self.context.total_timeout.map(|budget| Instant::now() + budget);
+
+        self.build(deadline)?;
+
+        let executable = self.get_executable()?;
+
+        let mut report =
+            self.build_runner(deadline).run_reporting(self.context.is_debug, &executable);
+
+        if report.passed() && !self.context.should_skip_anti_cheat {
+            let anti_cheat_outcomes =
+                self.build_anti_cheat_runner(deadline).run(self.context.is_debug, &executable);
+            report.steps.extend(anti_cheat_outcomes);
+        }
+
+        if self.is_json_output() {
+            // Written in one piece, straight to the process's stdout, for consumers parsing it.
+            let json = serde_json::to_string(&report.steps)?;
+            writeln!(io::stdout().lock(), "{json}")?;
+        }
+
+        if self.context.show_timing {
+            if self.is_json_output() {
+                eprint!("{}", report.timing_table());
+            } else {
+                print!("{}", report.timing_table());
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Runs the build command of the definition, if any, in the repository directory, stopping
+    /// it after [`BUILD_TIMEOUT`] or at `deadline`, whichever comes first.
+    fn build(&self, deadline: Option<Instant>) -> Result<()> {
+        let Some(command) = &self.definition.build_command else {
+            return Ok(());
+        };
+        let Some((program, args)) = command.split_first() else {
+            return Err(TesterError::InvalidDefinition("build command cannot be empty".into()));
+        };
+
+        let repository_dir = &self.context.repository_dir;
+        let path = find_program(program, repository_dir)
+            .ok_or_else(|| TesterError::ExecutableNotFound(PathBuf::from(program)))?;
+        let timeout = deadline.map_or(BUILD_TIMEOUT, |deadline| {
+            BUILD_TIMEOUT.min(deadline.saturating_duration_since(Instant::now()))
+        });
+        let mut executable =
+            Executable::new(path)?.with_timeout(timeout).with_working_dir(repository_dir.clone());
+
+        let (stdout, stderr, status) = match executable.run_owned(args) {
+            Ok(output) => output,
+            Err(TesterError::WaitTimeoutWithOutput { timeout, stdout, stderr }) => {
+                return Err(TesterError::BuildTimedOut {
+                    timeout,
+                    stdout: String::from_utf8_lossy(&stdout).into_owned(),
+                    stderr: String::from_utf8_lossy(&stderr).into_owned(),
+                });
+            }
+            Err(err) => return Err(err),
+        };
+
+        if !status.success() {
+            return Err(TesterError::BuildFailed {
+                status,
+                stdout: String::from_utf8_lossy(&stdout).into_owned(),
+                stderr: String::from_utf8_lossy(&stderr).into_owned(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the steps should only be listed rather than run.
+    pub fn is_list_only(&self) -> bool {
+        self.context.list_only
+    }
+
+    /// Renders the steps that would run, one per line as `[log_prefix] slug: title`, without
+    /// starting the executable. Anti-cheat steps are included unless they are skipped.
+    pub fn list_steps(&self) -> String {
+        let mut steps = self.collect_steps();
+        if !self.context.should_skip_anti_cheat {
+            steps.extend(self.collect_anti_cheat_steps());
+        }
+
+        steps
+            .iter()
+            .map(|step| format!("[{}] {}: {}\n", step.log_prefix, step.case.slug, step.title))
+            .collect()
     }
 
     /// Prints the debug context if debugging is enabled.
@@ -44,56 +164,221 @@ impl Tester {
             return;
         }
 
-        println!("{:?}", self.context);
+        if self.is_json_output() {
+            eprintln!("{:?}", self.context);
+        } else {
+            println!("{:?}", self.context);
+        }
+    }
+
+    /// Returns whether stdout is reserved for the JSON results, with text going to stderr.
+    fn is_json_output(&self) -> bool {
+        self.context.output_format == OutputFormat::Json
     }
 
     /// Collects steps by matching context cases with definition cases.
     fn collect_steps(&self) -> Vec<Step<'_>> {
-        self.context
+        self.collect_steps_with(|slug| self.definition.find_case(slug))
+    }
+
+    /// Collects anti-cheat steps by matching context cases with anti-cheat definition cases.
+    fn collect_anti_cheat_steps(&self) -> Vec<Step<'_>> {
+        self.collect_steps_with(|slug| self.definition.find_anti_cheat_case(slug))
+    }
+
+    /// Collects steps for the context cases that `find` resolves to a definition case, keeping
+    /// only the case named by `STACKCLASS_ONLY_SLUG` if set and only cases with a selected tag if
+    /// any are selected, sorted by log prefix if stage sorting
+    /// is enabled.
+    fn collect_steps_with<'a>(&'a self, find: impl Fn(&str) -> Option<&'a Case>) -> Vec<Step<'a>> {
+        let mut steps: Vec<Step<'a>> = self
+            .context
             .cases
             .iter()
+            .filter(|context_case| {
+                self.context.only_slug.as_ref().is_none_or(|slug| *slug == context_case.slug)
+            })
             .filter_map(|context_case| {
-                let definition_case = self.definition.find_case(&context_case.slug)?;
+                let definition_case = find(&context_case.slug)?;
+                if !self.is_selected(definition_case) {
+                    return None;
+                }
                 Some(Step {
                     case: definition_case,
                     log_prefix: &context_case.log_prefix,
                     title: &context_case.title,
                 })
             })
-            .collect()
+            .collect();
+
+        if self.context.sort_stages {
+            steps.sort_by(|a, b| natural_cmp(a.log_prefix, b.log_prefix));
+        }
+
+        steps
+    }
+
+    /// Checks whether a case carries one of the tags selected by `STACKCLASS_ONLY_TAGS`, or
+    /// whether no tags are selected at all.
+    fn is_selected(&self, case: &Case) -> bool {
+        self.context.only_tags.is_empty() ||
+            case.tags.iter().any(|tag| self.context.only_tags.contains(tag))
     }
 
     /// Builds a `Runner` from collected steps.
-    fn build_runner(&self) -> Runner<'_> {
-        Runner::new(self.collect_steps())
+    fn build_runner(&self, deadline: Option<Instant>) -> Runner<'_> {
+        self.configure_runner(Runner::new(self.collect_steps()), deadline)
+    }
+
+    /// Builds a quiet `Runner` from collected anti-cheat steps.
+    fn build_anti_cheat_runner(&self, deadline: Option<Instant>) -> Runner<'_> {
+        self.configure_runner(Runner::new_quiet(self.collect_anti_cheat_steps()), deadline)
+    }
+
+    /// Applies the context's settings to `runner`, ending its run at the shared `deadline` of
+    /// the total timeout, if any.
+    fn configure_runner<'a>(&self, runner: Runner<'a>, deadline: Option<Instant>) -> Runner<'a> {
+        let runner = runner
+            .with_timeout(self.context.timeout)
+            .with_repository_dir(&self.context.repository_dir)
+            .with_env(Arc::new(self.context.env.clone()))
+            .with_seed(self.seed.clone())
+            .with_text_to_stderr(self.is_json_output());
+
+        match (self.context.total_timeout, deadline) {
+            (Some(budget), Some(deadline)) => {
+                runner.with_total_timeout(budget).with_deadline(deadline)
+            }
+            _ => runner,
+        }
     }
 
-    /// Gets the executable from the context (verbose mode).
+    /// Gets the executable from the context (verbose mode), running in the repository directory
+    /// so that submissions can use paths relative to it.
     fn get_executable(&self) -> Result<Executable> {
-        Executable::new(self.context.executable_path.clone())
+        Ok(Executable::new(self.context.executable_path.clone())?
+            .with_working_dir(self.context.repository_dir.clone()))
     }
 
-    /// Validates that all test cases in the context have matching test cases in the definition.
-    /// Returns an error if any test case in the context does not match the definition.
+    /// Validates the structure of the definition (see [`Definition::validate`]) and that all test
+    /// cases in the context have matching test cases in the definition, either in
+    /// [`Definition::cases`] or in [`Definition::anti_cheat_cases`]. This is the check that keeps
+    /// an anti-cheat case the platform expects from being silently skipped.
+    /// Returns an error naming the first context case the definition does not have.
+    ///
+    /// In strict mode, it is also an error for a (non-anti-cheat) definition case to be missing
+    /// from the context. When unknown cases are ignored, context cases without a match are
+    /// logged instead, and never become steps.
     pub fn validate(&self) -> Result<()> {
+        let find = |slug: &str| {
+            self.definition.find_case(slug).or_else(|| self.definition.find_anti_cheat_case(slug))
+        };
+
+        self.definition.validate()?;
+
         for context_case in &self.context.cases {
-            let definition_case =
-                self.definition.find_case(&context_case.slug).ok_or_else(|| {
-                    format!(
-                        "tester context does not have test case with slug {}",
+            let Some(definition_case) = find(&context_case.slug) else {
+                if self.context.ignore_unknown_cases {
+                    warn!(
+                        "Ignoring test case with slug {}, which the test definition does not have",
                         context_case.slug
-                    )
-                })?;
+                    );
+                    continue;
+                }
+                return Err(format!(
+                    "test definition does not have a test case or anti-cheat test case with \
+                     slug {}",
+                    context_case.slug
+                )
+                .into());
+            };
 
             if definition_case.slug != context_case.slug {
                 return Err(format!(
-                    "tester context does not have test case with slug {}",
+                    "test definition does not have a test case or anti-cheat test case with \
+                     slug {}",
                     context_case.slug
                 )
                 .into());
             }
         }
 
+        if let Some(slug) = &self.context.only_slug {
+            let is_known = find(slug).is_some() &&
+                self.context.cases.iter().any(|context_case| context_case.slug == *slug);
+            if !is_known {
+                return Err(format!(
+                    "STACKCLASS_ONLY_SLUG is set to {slug}, which is not a test case of this run"
+                )
+                .into());
+            }
+        }
+
+        if self.context.strict_definition {
+            for definition_case in &self.definition.cases {
+                let is_referenced = self
+                    .context
+                    .cases
+                    .iter()
+                    .any(|context_case| context_case.slug == definition_case.slug);
+                if !is_referenced {
+                    return Err(format!(
+                        "test definition has test case with slug {} that the tester context \
+                         never references",
+                        definition_case.slug
+                    )
+                    .into());
+                }
+            }
+        }
+
         Ok(())
     }
 }
+
+/// Compares strings so that runs of digits are ordered by their numeric value, e.g. `stage-2`
+/// before `stage-10`.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(x), Some(y)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+
+        let ordering = if x.is_ascii_digit() && y.is_ascii_digit() {
+            let a_len = a.find(|c: char| !c.is_ascii_digit()).unwrap_or(a.len());
+            let b_len = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+            let (a_digits, b_digits) =
+                (a[..a_len].trim_start_matches('0'), b[..b_len].trim_start_matches('0'));
+            let ordering = a_digits.len().cmp(&b_digits.len()).then_with(|| a_digits.cmp(b_digits));
+            (a, b) = (&a[a_len..], &b[b_len..]);
+            ordering
+        } else {
+            (a, b) = (&a[x.len_utf8()..], &b[y.len_utf8()..]);
+            x.cmp(&y)
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Resolves `program` the way a shell would: a name containing a path separator is taken
+/// relative to `dir`, any other name is looked up in `PATH`.
+fn find_program(program: &str, dir: &Path) -> Option<PathBuf> {
+    if Path::new(program).components().count() > 1 {
+        let path = dir.join(program);
+        return executable::is_executable(&path).then_some(path);
+    }
+
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).find_map(|path| {
+        let path = path.join(program);
+        if executable::is_executable(&path) {
+            return Some(path);
+        }
+        let path = path.with_extension("exe");
+        (cfg!(windows) && executable::is_executable(&path)).then_some(path)
+    })
+}
@@ -0,0 +1,56 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tester::{assert_contains, assert_line_eq, normalize_newlines};
+
+#[test]
+fn test_assert_contains() {
+    assert!(assert_contains(b"hello world\n", b"world", "greeting").is_ok());
+
+    let err = assert_contains(b"hello world\n", b"planet", "greeting").unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains("greeting"), "{msg}");
+    assert!(msg.contains("\"planet\""), "{msg}");
+    assert!(msg.contains("\"hello world\\n\""), "{msg}");
+}
+
+#[test]
+fn test_assert_contains_non_utf8_and_truncated() {
+    let mut haystack = vec![0xff, 0xfe];
+    haystack.extend(std::iter::repeat_n(b'a', 500));
+
+    let err = assert_contains(&haystack, b"b", "binary").unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains('\u{fffd}'), "{msg}");
+    assert!(msg.contains("... (502 bytes total)"), "{msg}");
+    assert!(!msg.contains(&"a".repeat(250)), "{msg}");
+}
+
+#[test]
+fn test_assert_line_eq() {
+    assert!(assert_line_eq(b"PONG\r\n", "PONG", "ping").is_ok());
+    assert!(assert_line_eq(b"PONG", "PONG", "ping").is_ok());
+
+    let err = assert_line_eq(b"P\xffNG\n", "PONG", "ping").unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains("ping: expected line \"PONG\""), "{msg}");
+    assert!(msg.contains("P\u{fffd}NG"), "{msg}");
+}
+
+#[test]
+fn test_normalize_newlines() {
+    assert_eq!(normalize_newlines(b"a\r\nb\r\n"), b"a\nb\n");
+    assert_eq!(normalize_newlines(b"a\rb\n\r"), b"a\rb\n\r");
+    assert_eq!(normalize_newlines(b"\r\r\n"), b"\r\n");
+}
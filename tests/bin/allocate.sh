@@ -0,0 +1,3 @@
+#!/bin/sh
+buffer=$(head -c 200000000 /dev/zero | tr '\0' a)
+echo "allocated ${#buffer} bytes"
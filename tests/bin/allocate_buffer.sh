@@ -0,0 +1,2 @@
+#!/bin/sh
+exec dd if=/dev/zero of=/dev/null bs=200M count=1
@@ -0,0 +1,10 @@
+#!/bin/sh
+echo out1
+sleep 0.1
+echo err1 >&2
+sleep 0.1
+echo out2
+sleep 0.1
+echo err2 >&2
+sleep 0.1
+echo done
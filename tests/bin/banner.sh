@@ -0,0 +1,5 @@
+#!/bin/sh
+echo "starting"
+sleep 0.5
+echo "Listening on 1234"
+echo "ready"
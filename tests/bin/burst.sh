@@ -0,0 +1,3 @@
+#!/bin/sh
+printf 'out'
+printf 'err' >&2
@@ -0,0 +1,8 @@
+#!/bin/sh
+i=1
+while [ "$i" -le 15 ]; do
+    echo "trace line $i" >&2
+    i=$((i + 1))
+done
+echo "panic: boom" >&2
+exit 3
@@ -0,0 +1,4 @@
+#!/bin/sh
+for name in "$@"; do
+    eval "echo \"$name=\${$name}\""
+done
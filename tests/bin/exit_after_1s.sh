@@ -0,0 +1,4 @@
+#!/bin/sh
+sleep 1
+echo "panic: idle connection" >&2
+exit 3
@@ -0,0 +1,2 @@
+#!/bin/sh
+head -c 4000000 /dev/zero
@@ -0,0 +1,6 @@
+#!/bin/sh
+printf '\001\002\003'
+sleep 0.2
+printf '\004\005'
+sleep 0.2
+printf '\006\007\010'
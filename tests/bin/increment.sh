@@ -0,0 +1,4 @@
+#!/bin/sh
+while read -r n; do
+    echo $((n + 1))
+done
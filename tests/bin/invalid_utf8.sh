@@ -0,0 +1,2 @@
+#!/bin/sh
+printf 'caf\303\251\377\n'
@@ -0,0 +1,8 @@
+#!/bin/sh
+if [ -f state.txt ]; then
+    echo "recovered $(cat state.txt)"
+else
+    echo "$1" > state.txt
+    echo "saved $1"
+fi
+sleep 10
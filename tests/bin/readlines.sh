@@ -0,0 +1,5 @@
+#!/bin/sh
+read -r first
+echo "$first"
+read -r second
+echo "$second"
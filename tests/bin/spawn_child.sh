@@ -0,0 +1,4 @@
+#!/bin/sh
+sleep 10 &
+echo "started"
+wait
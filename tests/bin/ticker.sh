@@ -0,0 +1,4 @@
+#!/bin/sh
+echo one
+sleep 1
+echo two
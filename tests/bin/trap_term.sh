@@ -0,0 +1,6 @@
+#!/bin/sh
+trap 'echo "terminated"; exit 0' TERM
+echo "ready"
+while true; do
+    sleep 0.1
+done
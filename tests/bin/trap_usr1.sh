@@ -0,0 +1,6 @@
+#!/bin/sh
+trap 'echo "stats"' USR1
+echo "ready"
+while true; do
+    sleep 0.1
+done
@@ -12,9 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashMap, process::ExitCode, sync::Arc};
+use std::{
+    collections::HashMap,
+    process::ExitCode,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
 
-use tester::{Case, CaseError, Definition, Harness, run};
+use tester::{
+    Case, CaseError, Definition, Harness, Tester, TesterError, color_enabled, run, run_with_report,
+};
 
 fn pass_func(_harness: &Harness) -> Result<(), CaseError> {
     Ok(())
@@ -56,6 +65,35 @@ fn test_all_stages_pass() {
     assert_eq!(exit_code, ExitCode::SUCCESS);
 }
 
+#[test]
+fn test_run_with_report() {
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        (
+            "STACKCLASS_TEST_CASES_JSON".to_string(),
+            build_test_cases_json(&["test-1", "test-2", "anti-cheat-1"]),
+        ),
+    ]);
+
+    let definition = Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![
+            Case::new("test-1", Arc::new(pass_func)),
+            Case::new("test-2", Arc::new(pass_func)),
+        ],
+        anti_cheat_cases: vec![Case::new("anti-cheat-1", Arc::new(fail_func))],
+        ..Default::default()
+    };
+
+    let report = run_with_report(env, definition).unwrap();
+    assert!(!report.passed());
+
+    let outcomes: Vec<_> =
+        report.steps.iter().map(|step| (step.slug.as_str(), step.passed)).collect();
+    assert_eq!(outcomes, [("test-1", true), ("test-2", true), ("anti-cheat-1", false)]);
+    assert_eq!(report.steps[2].error.as_deref(), Some("fail"));
+}
+
 #[test]
 fn test_one_stage_fails() {
     let env = HashMap::from([
@@ -75,3 +113,432 @@ fn test_one_stage_fails() {
     let exit_code = run(env, definition);
     assert_eq!(exit_code, ExitCode::FAILURE);
 }
+
+#[test]
+fn test_anti_cheat_stage_fails() {
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        (
+            "STACKCLASS_TEST_CASES_JSON".to_string(),
+            build_test_cases_json(&["test-1", "anti-cheat-1"]),
+        ),
+    ]);
+
+    let definition = Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![Case::new("test-1", Arc::new(pass_func))],
+        anti_cheat_cases: vec![Case::new("anti-cheat-1", Arc::new(fail_func))],
+        ..Default::default()
+    };
+
+    let exit_code = run(env, definition);
+    assert_eq!(exit_code, ExitCode::FAILURE);
+}
+
+#[test]
+fn test_anti_cheat_stage_skipped() {
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        (
+            "STACKCLASS_TEST_CASES_JSON".to_string(),
+            build_test_cases_json(&["test-1", "anti-cheat-1"]),
+        ),
+        ("STACKCLASS_SKIP_ANTI_CHEAT".to_string(), "true".to_string()),
+    ]);
+
+    let definition = Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![Case::new("test-1", Arc::new(pass_func))],
+        anti_cheat_cases: vec![Case::new("anti-cheat-1", Arc::new(fail_func))],
+        ..Default::default()
+    };
+
+    let exit_code = run(env, definition);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+}
+
+#[test]
+fn test_duplicate_slugs_fail_validation() {
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        ("STACKCLASS_TEST_CASES_JSON".to_string(), build_test_cases_json(&["test-1"])),
+    ]);
+
+    let definition = Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![
+            Case::new("test-1", Arc::new(pass_func)),
+            Case::new("test-1", Arc::new(pass_func)),
+        ],
+        ..Default::default()
+    };
+
+    let exit_code = run(env, definition);
+    assert_eq!(exit_code, ExitCode::FAILURE);
+}
+
+#[test]
+fn test_list_only() {
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        (
+            "STACKCLASS_TEST_CASES_JSON".to_string(),
+            build_test_cases_json(&["test-1", "test-2", "anti-cheat-1"]),
+        ),
+        ("STACKCLASS_LIST_ONLY".to_string(), "true".to_string()),
+    ]);
+
+    let ran = Arc::new(AtomicBool::new(false));
+    let case = |slug: &str| {
+        let ran = ran.clone();
+        Case::new(
+            slug,
+            Arc::new(move |_: &Harness| -> Result<(), CaseError> {
+                ran.store(true, Ordering::SeqCst);
+                Err("should not run".to_string().into())
+            }),
+        )
+    };
+
+    let definition = || Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![case("test-1"), case("test-2")],
+        anti_cheat_cases: vec![case("anti-cheat-1")],
+        ..Default::default()
+    };
+
+    let tester = Tester::new(env.clone(), definition()).unwrap();
+    assert!(tester.is_list_only());
+    assert_eq!(
+        tester.list_steps(),
+        "[test-1] test-1: Stage #1: test-1\n\
+         [test-2] test-2: Stage #2: test-2\n\
+         [test-3] anti-cheat-1: Stage #3: anti-cheat-1\n"
+    );
+
+    let exit_code = run(env, definition());
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert!(!ran.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_strict_definition() {
+    let mut env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        ("STACKCLASS_TEST_CASES_JSON".to_string(), build_test_cases_json(&["test-1"])),
+        ("STACKCLASS_STRICT_DEFINITION".to_string(), "true".to_string()),
+    ]);
+
+    let definition = || Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![Case::new("test-1", Arc::new(pass_func))],
+        anti_cheat_cases: vec![Case::new("anti-cheat-1", Arc::new(pass_func))],
+        ..Default::default()
+    };
+    assert!(Tester::new(env.clone(), definition()).unwrap().validate().is_ok());
+
+    let orphaned = || Definition {
+        cases: vec![
+            Case::new("test-1", Arc::new(pass_func)),
+            Case::new("test-2", Arc::new(pass_func)),
+        ],
+        ..definition()
+    };
+    let err = Tester::new(env.clone(), orphaned()).unwrap().validate().unwrap_err();
+    assert!(err.to_string().contains("test-2"), "{err}");
+
+    env.remove("STACKCLASS_STRICT_DEFINITION");
+    assert!(Tester::new(env, orphaned()).unwrap().validate().is_ok());
+}
+
+#[test]
+fn test_ignore_unknown_cases() {
+    let mut env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        ("STACKCLASS_TEST_CASES_JSON".to_string(), build_test_cases_json(&["test-1", "test-2"])),
+    ]);
+
+    let definition = || Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![Case::new("test-1", Arc::new(pass_func))],
+        ..Default::default()
+    };
+
+    let err = Tester::new(env.clone(), definition()).unwrap().validate().unwrap_err();
+    assert!(err.to_string().contains("test-2"), "{err}");
+
+    env.insert("STACKCLASS_IGNORE_UNKNOWN_CASES".to_string(), "true".to_string());
+    let tester = Tester::new(env, definition()).unwrap();
+    assert!(tester.validate().is_ok());
+    assert_eq!(tester.list_steps(), "[test-1] test-1: Stage #1: test-1\n");
+}
+
+#[test]
+fn test_color_enabled() {
+    let mut env = HashMap::new();
+    assert!(color_enabled(&env, true));
+    assert!(!color_enabled(&env, false));
+
+    env.insert("NO_COLOR".to_string(), "1".to_string());
+    assert!(!color_enabled(&env, true));
+
+    // An empty `NO_COLOR` is treated as unset.
+    env.insert("NO_COLOR".to_string(), String::new());
+    assert!(color_enabled(&env, true));
+}
+
+#[test]
+fn test_validate_anti_cheat_case_present() {
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        (
+            "STACKCLASS_TEST_CASES_JSON".to_string(),
+            build_test_cases_json(&["test-1", "anti-cheat-1"]),
+        ),
+    ]);
+    let definition = Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![Case::new("test-1", Arc::new(pass_func))],
+        anti_cheat_cases: vec![Case::new("anti-cheat-1", Arc::new(pass_func))],
+        ..Default::default()
+    };
+
+    let report = run_with_report(env, definition).unwrap();
+    let outcomes: Vec<_> = report.steps.iter().map(|step| step.slug.as_str()).collect();
+    assert_eq!(outcomes, ["test-1", "anti-cheat-1"]);
+}
+
+#[test]
+fn test_validate_anti_cheat_case_missing() {
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        (
+            "STACKCLASS_TEST_CASES_JSON".to_string(),
+            build_test_cases_json(&["test-1", "anti-cheat-1"]),
+        ),
+    ]);
+    let definition = Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![Case::new("test-1", Arc::new(pass_func))],
+        ..Default::default()
+    };
+
+    let err = Tester::new(env, definition).unwrap().validate().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "test definition does not have a test case or anti-cheat test case with slug anti-cheat-1"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_program_runs_in_repository_dir() {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    fn run_program(harness: &Harness) -> Result<(), CaseError> {
+        harness.new_executable().run(&[])?;
+        Ok(())
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let program = dir.path().join("your_program.sh");
+    fs::write(&program, "#!/bin/sh\necho hello > output.txt\n").unwrap();
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), dir.path().display().to_string()),
+        ("STACKCLASS_TEST_CASES_JSON".to_string(), build_test_cases_json(&["test-1"])),
+    ]);
+    let definition = Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![Case::new("test-1", Arc::new(run_program))],
+        ..Default::default()
+    };
+
+    assert!(run_with_report(env, definition).unwrap().passed());
+    assert_eq!(fs::read_to_string(dir.path().join("output.txt")).unwrap(), "hello\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_build_command() {
+    use std::{
+        fs,
+        os::unix::fs::PermissionsExt,
+        time::{Duration, Instant},
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let program = dir.path().join("your_program.sh");
+    fs::write(&program, "#!/bin/sh\n").unwrap();
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+    let env = || {
+        HashMap::from([
+            ("STACKCLASS_REPOSITORY_DIR".to_string(), dir.path().display().to_string()),
+            ("STACKCLASS_TEST_CASES_JSON".to_string(), build_test_cases_json(&["test-1"])),
+        ])
+    };
+    let definition = |command: &str| Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![Case::new("test-1", Arc::new(pass_func))],
+        build_command: Some(vec!["sh".to_string(), "-c".to_string(), command.to_string()]),
+        ..Default::default()
+    };
+
+    assert!(run_with_report(env(), definition("echo built > build.txt")).unwrap().passed());
+    assert_eq!(fs::read_to_string(dir.path().join("build.txt")).unwrap(), "built\n");
+
+    let command = "echo 'compiling'; echo 'syntax error' >&2; exit 2";
+    let err = run_with_report(env(), definition(command)).unwrap_err();
+    let message = err.to_string();
+    assert!(message.starts_with("Build command failed (exit status: 2)"), "{message}");
+    assert!(message.contains("stdout:\ncompiling"), "{message}");
+    assert!(message.contains("stderr:\nsyntax error"), "{message}");
+
+    // The build is stopped once the total timeout runs out.
+    let mut env = env();
+    env.insert("STACKCLASS_TOTAL_TIMEOUT_SECONDS".to_string(), "1".to_string());
+    let start = Instant::now();
+    let err = run_with_report(env, definition("echo 'started'; sleep 10")).unwrap_err();
+    assert!(start.elapsed() < Duration::from_secs(5), "{:?}", start.elapsed());
+    assert!(matches!(err, TesterError::BuildTimedOut { .. }), "{err:?}");
+    assert!(err.to_string().contains("stdout:\nstarted"), "{err}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_total_timeout_covers_build_and_anti_cheat() {
+    use std::time::{Duration, Instant};
+
+    fn slow_func(_harness: &Harness) -> Result<(), CaseError> {
+        std::thread::sleep(Duration::from_millis(300));
+        Ok(())
+    }
+
+    fn hanging_func(_harness: &Harness) -> Result<(), CaseError> {
+        std::thread::sleep(Duration::from_secs(3));
+        Ok(())
+    }
+
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        (
+            "STACKCLASS_TEST_CASES_JSON".to_string(),
+            build_test_cases_json(&["test-1", "anti-cheat-1"]),
+        ),
+        ("STACKCLASS_TOTAL_TIMEOUT_SECONDS".to_string(), "1".to_string()),
+    ]);
+    let definition = Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![Case::new("test-1", Arc::new(slow_func))],
+        anti_cheat_cases: vec![Case::new("anti-cheat-1", Arc::new(hanging_func))],
+        build_command: Some(vec!["sleep".to_string(), "0.5".to_string()]),
+        ..Default::default()
+    };
+
+    let start = Instant::now();
+    let report = run_with_report(env, definition).unwrap();
+    assert!(start.elapsed() < Duration::from_millis(1500), "{:?}", start.elapsed());
+
+    let passed: Vec<_> = report.steps.iter().map(|step| step.passed).collect();
+    assert_eq!(passed, [true, false]);
+    let error = report.steps[1].error.as_deref().unwrap();
+    assert!(error.starts_with("interrupted after "), "{error}");
+}
+
+#[test]
+fn test_only_slug() {
+    let mut env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        ("STACKCLASS_TEST_CASES_JSON".to_string(), build_test_cases_json(&["test-1", "test-2"])),
+        ("STACKCLASS_ONLY_SLUG".to_string(), "test-2".to_string()),
+    ]);
+
+    let definition = || Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![
+            Case::new("test-1", Arc::new(pass_func)),
+            Case::new("test-2", Arc::new(pass_func)),
+        ],
+        ..Default::default()
+    };
+
+    let tester = Tester::new(env.clone(), definition()).unwrap();
+    assert!(tester.validate().is_ok());
+    assert_eq!(tester.list_steps(), "[test-2] test-2: Stage #2: test-2\n");
+
+    env.insert("STACKCLASS_ONLY_SLUG".to_string(), "test-3".to_string());
+    let err = Tester::new(env, definition()).unwrap().validate().unwrap_err();
+    assert!(err.to_string().contains("STACKCLASS_ONLY_SLUG is set to test-3"), "{err}");
+}
+
+#[test]
+fn test_sort_stages() {
+    let test_cases = serde_json::json!([
+        { "slug": "test-10", "log_prefix": "stage-10", "title": "Stage #10" },
+        { "slug": "test-2", "log_prefix": "stage-2", "title": "Stage #2" },
+        { "slug": "test-1", "log_prefix": "stage-1", "title": "Stage #1" },
+    ]);
+    let mut env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        ("STACKCLASS_TEST_CASES_JSON".to_string(), test_cases.to_string()),
+    ]);
+
+    let definition = || Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![
+            Case::new("test-1", Arc::new(pass_func)),
+            Case::new("test-2", Arc::new(pass_func)),
+            Case::new("test-10", Arc::new(pass_func)),
+        ],
+        ..Default::default()
+    };
+
+    let tester = Tester::new(env.clone(), definition()).unwrap();
+    assert_eq!(
+        tester.list_steps(),
+        "[stage-10] test-10: Stage #10\n[stage-2] test-2: Stage #2\n[stage-1] test-1: Stage #1\n"
+    );
+
+    env.insert("STACKCLASS_SORT_STAGES".to_string(), "true".to_string());
+    let tester = Tester::new(env, definition()).unwrap();
+    assert_eq!(
+        tester.list_steps(),
+        "[stage-1] test-1: Stage #1\n[stage-2] test-2: Stage #2\n[stage-10] test-10: Stage #10\n"
+    );
+}
+
+#[test]
+fn test_only_tags() {
+    let mut env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        (
+            "STACKCLASS_TEST_CASES_JSON".to_string(),
+            build_test_cases_json(&["test-1", "test-2", "test-3"]),
+        ),
+    ]);
+
+    let definition = || Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![
+            Case::new("test-1", Arc::new(pass_func)).with_tag("basics"),
+            Case::new("test-2", Arc::new(pass_func)).with_tag("network").with_tag("slow"),
+            Case::new("test-3", Arc::new(pass_func)),
+        ],
+        ..Default::default()
+    };
+
+    let tester = Tester::new(env.clone(), definition()).unwrap();
+    assert_eq!(tester.list_steps().lines().count(), 3);
+
+    env.insert("STACKCLASS_ONLY_TAGS".to_string(), "network, basics".to_string());
+    let tester = Tester::new(env.clone(), definition()).unwrap();
+    assert_eq!(
+        tester.list_steps(),
+        "[test-1] test-1: Stage #1: test-1\n[test-2] test-2: Stage #2: test-2\n"
+    );
+
+    env.insert("STACKCLASS_ONLY_TAGS".to_string(), "slow".to_string());
+    let tester = Tester::new(env, definition()).unwrap();
+    assert_eq!(tester.list_steps(), "[test-2] test-2: Stage #2: test-2\n");
+}
@@ -12,9 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use tester::{Context, Definition, TesterError};
+use tester::{Context, Definition, OutputFormat, TesterError};
 
 use std::collections::HashMap;
+#[cfg(unix)]
+use std::{fs, path::Path};
+
+fn echo_definition() -> Definition {
+    Definition { executable_name: "your_program.sh".to_string(), ..Default::default() }
+}
+
+#[cfg(unix)]
+fn write_script(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::write(path, "#!/bin/sh\necho \"$@\"\n").unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[cfg(unix)]
+fn env_for_dir(dir: &Path) -> HashMap<String, String> {
+    HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), dir.to_string_lossy().into_owned()),
+        (
+            "STACKCLASS_TEST_CASES_JSON".to_string(),
+            r#"[{ "slug": "test", "log_prefix": "test", "title": "Test" }]"#.to_string(),
+        ),
+    ])
+}
 
 #[test]
 fn test_requires_app_dir() {
@@ -23,7 +48,7 @@ fn test_requires_app_dir() {
         r#"[{ "slug": "test", "log_prefix": "test", "title": "Test" }]"#.to_string(),
     )]);
 
-    let result = Context::from_env(env, &Definition::default());
+    let result = Context::from_env(env, &echo_definition());
     assert!(result.is_err(), "Expected an error due to missing STACKCLASS_REPOSITORY_DIR");
 
     if let Err(err) = result {
@@ -44,7 +69,7 @@ fn test_valid_test_cases() {
         ),
     ]);
 
-    let result = Context::from_env(env, &Definition::default());
+    let result = Context::from_env(env, &echo_definition());
     assert!(result.is_ok(), "Expected successful context creation");
 }
 
@@ -55,7 +80,7 @@ fn test_empty_test_cases() {
         ("STACKCLASS_TEST_CASES_JSON".to_string(), "[]".to_string()),
     ]);
 
-    let result = Context::from_env(env, &Definition::default());
+    let result = Context::from_env(env, &echo_definition());
     assert!(matches!(result, Err(TesterError::InvalidTestCase(_))));
 }
 
@@ -73,7 +98,7 @@ fn test_invalid_test_case_fields() {
             ("STACKCLASS_TEST_CASES_JSON".to_string(), json.to_string()),
         ]);
 
-        let result = Context::from_env(env, &Definition::default());
+        let result = Context::from_env(env, &echo_definition());
         assert!(
             matches!(result, Err(TesterError::InvalidTestCase(_))),
             "Expected InvalidTestCase for case: {description}",
@@ -81,6 +106,57 @@ fn test_invalid_test_case_fields() {
     }
 }
 
+#[test]
+fn test_malformed_test_cases_json() {
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        ("STACKCLASS_TEST_CASES_JSON".to_string(), "[\n  { \"slug\": \"test\",\n]".to_string()),
+    ]);
+
+    let err = Context::from_env(env, &echo_definition()).unwrap_err();
+    assert!(matches!(err, TesterError::JsonParse(_)), "Expected JsonParse, got {err:?}");
+
+    let message = err.to_string();
+    assert!(message.contains("failed to parse STACKCLASS_TEST_CASES_JSON: "), "{message}");
+    assert!(message.contains("line 3"), "{message}");
+}
+
+#[test]
+fn test_unknown_test_case_field() {
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        (
+            "STACKCLASS_TEST_CASES_JSON".to_string(),
+            r#"[{ "slug": "test", "prefix": "test", "title": "Test" }]"#.to_string(),
+        ),
+    ]);
+
+    let err = Context::from_env(env, &echo_definition()).unwrap_err();
+    assert!(matches!(err, TesterError::JsonParse(_)), "Expected JsonParse, got {err:?}");
+    assert!(err.to_string().contains("unknown field `prefix`"), "{err}");
+}
+
+#[test]
+fn test_test_cases_json_not_an_array() {
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        (
+            "STACKCLASS_TEST_CASES_JSON".to_string(),
+            r#"{ "slug": "test", "log_prefix": "test", "title": "Test" }"#.to_string(),
+        ),
+    ]);
+
+    let err = Context::from_env(env, &echo_definition()).unwrap_err();
+    assert!(matches!(err, TesterError::JsonParse(_)), "Expected JsonParse, got {err:?}");
+    assert!(
+        err.to_string().ends_with(
+            "failed to parse STACKCLASS_TEST_CASES_JSON: expected a JSON array of test cases but \
+             found an object"
+        ),
+        "{err}"
+    );
+}
+
 #[test]
 fn test_executable_not_found() {
     let env = HashMap::from([
@@ -91,7 +167,7 @@ fn test_executable_not_found() {
         ),
     ]);
 
-    let result = Context::from_env(env, &Definition::default());
+    let result = Context::from_env(env, &echo_definition());
     assert!(matches!(result, Err(TesterError::ExecutableNotFound(_))));
 }
 
@@ -106,8 +182,18 @@ fn test_timeout_parsing() {
         ("STACKCLASS_TIMEOUT_SECONDS".to_string(), "30".to_string()),
     ]);
 
-    let context = Context::from_env(env, &Definition::default()).unwrap();
+    let context = Context::from_env(env.clone(), &echo_definition()).unwrap();
     assert_eq!(context.timeout, std::time::Duration::from_secs(30));
+    assert_eq!(context.total_timeout, None);
+
+    let mut env = env;
+    env.insert("STACKCLASS_TOTAL_TIMEOUT_SECONDS".to_string(), "120".to_string());
+    let context = Context::from_env(env.clone(), &echo_definition()).unwrap();
+    assert_eq!(context.total_timeout, Some(std::time::Duration::from_secs(120)));
+
+    env.insert("STACKCLASS_TOTAL_TIMEOUT_SECONDS".to_string(), "0".to_string());
+    let err = Context::from_env(env, &echo_definition()).unwrap_err();
+    assert!(err.to_string().contains("STACKCLASS_TOTAL_TIMEOUT_SECONDS"), "{err}");
 }
 
 #[test]
@@ -121,7 +207,7 @@ fn test_debug_flag() {
         ("STACKCLASS_DEBUG".to_string(), "true".to_string()),
     ]);
 
-    let context = Context::from_env(env, &Definition::default()).unwrap();
+    let context = Context::from_env(env, &echo_definition()).unwrap();
     assert!(context.is_debug);
 }
 
@@ -136,6 +222,286 @@ fn test_skip_anti_cheat_flag() {
         ("STACKCLASS_SKIP_ANTI_CHEAT".to_string(), "true".to_string()),
     ]);
 
-    let context = Context::from_env(env, &Definition::default()).unwrap();
+    let context = Context::from_env(env, &echo_definition()).unwrap();
     assert!(context.should_skip_anti_cheat);
 }
+
+#[cfg(unix)]
+#[test]
+fn test_find_executable_in_repository_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("your_program.sh");
+    write_script(&script);
+
+    let context = Context::from_env(env_for_dir(dir.path()), &echo_definition()).unwrap();
+    assert_eq!(context.executable_path, script.canonicalize().unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_find_executable_falls_back_to_legacy_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("legacy_program.sh");
+    write_script(&script);
+
+    let definition = Definition {
+        legacy_executable_name: Some("legacy_program.sh".to_string()),
+        ..echo_definition()
+    };
+
+    let context = Context::from_env(env_for_dir(dir.path()), &definition).unwrap();
+    assert_eq!(context.executable_path, script.canonicalize().unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_find_executable_prefers_primary_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("your_program.sh");
+    write_script(&script);
+    write_script(&dir.path().join("legacy_program.sh"));
+
+    let definition = Definition {
+        legacy_executable_name: Some("legacy_program.sh".to_string()),
+        ..echo_definition()
+    };
+
+    let context = Context::from_env(env_for_dir(dir.path()), &definition).unwrap();
+    assert_eq!(context.executable_path, script.canonicalize().unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_find_executable_without_primary_or_legacy() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let definition = Definition {
+        legacy_executable_name: Some("legacy_program.sh".to_string()),
+        ..echo_definition()
+    };
+
+    let result = Context::from_env(env_for_dir(dir.path()), &definition);
+    let expected = dir.path().join("your_program.sh");
+    assert!(matches!(result, Err(TesterError::ExecutableNotFound(path)) if path == expected));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_find_executable_rejects_non_executable_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("your_program.sh");
+    fs::write(&script, "#!/bin/sh\n").unwrap();
+
+    let result = Context::from_env(env_for_dir(dir.path()), &echo_definition());
+    assert!(matches!(result, Err(TesterError::ExecutableNotFound(path)) if path == script));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_find_executable_rejects_traversal() {
+    let dir = tempfile::tempdir().unwrap();
+    let repository_dir = dir.path().join("repo");
+    fs::create_dir(&repository_dir).unwrap();
+    write_script(&dir.path().join("outside.sh"));
+
+    let definition =
+        Definition { executable_name: "../outside.sh".to_string(), ..echo_definition() };
+
+    let result = Context::from_env(env_for_dir(&repository_dir), &definition);
+    assert!(matches!(result, Err(TesterError::InvalidPath(_))), "{result:?}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_find_executable_accepts_symlink_to_outside() {
+    let dir = tempfile::tempdir().unwrap();
+    let repository_dir = dir.path().join("repo");
+    fs::create_dir(&repository_dir).unwrap();
+    write_script(&dir.path().join("build.sh"));
+    let link = repository_dir.join("your_program.sh");
+    std::os::unix::fs::symlink(dir.path().join("build.sh"), &link).unwrap();
+
+    let context = Context::from_env(env_for_dir(&repository_dir), &echo_definition()).unwrap();
+    assert_eq!(
+        context.executable_path,
+        repository_dir.canonicalize().unwrap().join("your_program.sh")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_find_executable_falls_back_after_traversal() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("legacy_program.sh");
+    write_script(&script);
+
+    let definition = Definition {
+        executable_name: "../your_program.sh".to_string(),
+        legacy_executable_name: Some("legacy_program.sh".to_string()),
+        ..Default::default()
+    };
+
+    let context = Context::from_env(env_for_dir(dir.path()), &definition).unwrap();
+    assert_eq!(context.executable_path, script.canonicalize().unwrap());
+}
+
+#[test]
+fn test_output_format() {
+    let mut env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        (
+            "STACKCLASS_TEST_CASES_JSON".to_string(),
+            r#"[{ "slug": "test", "log_prefix": "test", "title": "Test" }]"#.to_string(),
+        ),
+    ]);
+
+    let context = Context::from_env(env.clone(), &echo_definition()).unwrap();
+    assert_eq!(context.output_format, OutputFormat::Text);
+
+    env.insert("STACKCLASS_OUTPUT_FORMAT".to_string(), "json".to_string());
+    let context = Context::from_env(env, &echo_definition()).unwrap();
+    assert_eq!(context.output_format, OutputFormat::Json);
+}
+
+#[test]
+fn test_show_timing() {
+    let mut env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        (
+            "STACKCLASS_TEST_CASES_JSON".to_string(),
+            r#"[{ "slug": "test", "log_prefix": "test", "title": "Test" }]"#.to_string(),
+        ),
+    ]);
+
+    let context = Context::from_env(env.clone(), &echo_definition()).unwrap();
+    assert!(!context.show_timing);
+
+    env.insert("STACKCLASS_TIMING".to_string(), "true".to_string());
+    let context = Context::from_env(env, &echo_definition()).unwrap();
+    assert!(context.show_timing);
+}
+
+#[test]
+fn test_test_cases_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("test_cases.json");
+    std::fs::write(&file, r#"[{ "slug": "from-file", "log_prefix": "test", "title": "Test" }]"#)
+        .unwrap();
+
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        ("STACKCLASS_TEST_CASES_FILE".to_string(), file.to_string_lossy().into_owned()),
+    ]);
+
+    let context = Context::from_env(env, &echo_definition()).unwrap();
+    assert_eq!(context.cases.len(), 1);
+    assert_eq!(context.cases[0].slug, "from-file");
+}
+
+#[test]
+fn test_missing_test_cases_file() {
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        ("STACKCLASS_TEST_CASES_FILE".to_string(), "/nonexistent/test_cases.json".to_string()),
+    ]);
+
+    let result = Context::from_env(env, &echo_definition());
+    assert!(matches!(result, Err(TesterError::Io(_))));
+}
+
+#[test]
+fn test_test_cases_file_and_json_precedence() {
+    let json = r#"[{ "slug": "test", "log_prefix": "test", "title": "Test" }]"#;
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("test_cases.json");
+
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        ("STACKCLASS_TEST_CASES_JSON".to_string(), json.to_string()),
+        ("STACKCLASS_TEST_CASES_FILE".to_string(), file.to_string_lossy().into_owned()),
+    ]);
+
+    // Matching content in both sources is accepted.
+    std::fs::write(&file, json).unwrap();
+    assert!(Context::from_env(env.clone(), &echo_definition()).is_ok());
+
+    // Conflicting content is rejected.
+    std::fs::write(&file, r#"[{ "slug": "other", "log_prefix": "test", "title": "Test" }]"#)
+        .unwrap();
+    let result = Context::from_env(env, &echo_definition());
+    assert!(matches!(result, Err(TesterError::InvalidTestCase(_))));
+}
+
+#[test]
+fn test_boolean_flag_spellings() {
+    let accepted = ["true", "TRUE", "True", "1", "yes", "YES", "on", "On"];
+    let rejected = ["false", "0", "no", "off", "", "enabled", "y"];
+
+    for (value, expected) in
+        accepted.iter().map(|v| (v, true)).chain(rejected.iter().map(|v| (v, false)))
+    {
+        let env = HashMap::from([
+            ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+            (
+                "STACKCLASS_TEST_CASES_JSON".to_string(),
+                r#"[{ "slug": "test", "log_prefix": "test", "title": "Test" }]"#.to_string(),
+            ),
+            ("STACKCLASS_DEBUG".to_string(), value.to_string()),
+            ("STACKCLASS_SKIP_ANTI_CHEAT".to_string(), value.to_string()),
+        ]);
+
+        let context = Context::from_env(env, &echo_definition()).unwrap();
+        assert_eq!(context.is_debug, expected, "STACKCLASS_DEBUG={value:?}");
+        assert_eq!(
+            context.should_skip_anti_cheat, expected,
+            "STACKCLASS_SKIP_ANTI_CHEAT={value:?}"
+        );
+    }
+}
+
+#[test]
+fn test_invalid_timeout() {
+    for value in ["3o", "-1", "1.5", "0"] {
+        let env = HashMap::from([
+            ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+            (
+                "STACKCLASS_TEST_CASES_JSON".to_string(),
+                r#"[{ "slug": "test", "log_prefix": "test", "title": "Test" }]"#.to_string(),
+            ),
+            ("STACKCLASS_TIMEOUT_SECONDS".to_string(), value.to_string()),
+        ]);
+
+        let result = Context::from_env(env, &echo_definition());
+        assert!(
+            matches!(result, Err(TesterError::InvalidConfig(_))),
+            "Expected InvalidConfig for STACKCLASS_TIMEOUT_SECONDS={value:?}",
+        );
+    }
+}
+
+// macOS file systems reject names that are not valid UTF-8.
+#[cfg(target_os = "linux")]
+#[test]
+fn test_from_env_os_with_non_utf8_repository_dir() {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+    let dir = tempfile::tempdir().unwrap();
+    let repository_dir = dir.path().join(OsString::from_vec(b"repo-\xff".to_vec()));
+    fs::create_dir(&repository_dir).unwrap();
+    write_script(&repository_dir.join("your_program.sh"));
+
+    let mut env: HashMap<OsString, OsString> = env_for_dir(dir.path())
+        .into_iter()
+        .map(|(key, value)| (key.into(), value.into()))
+        .collect();
+    env.insert("STACKCLASS_REPOSITORY_DIR".into(), repository_dir.clone().into_os_string());
+
+    let context = Context::from_env_os(env.clone(), &echo_definition()).unwrap();
+    assert_eq!(context.repository_dir, repository_dir);
+    assert!(context.executable_path.ends_with("your_program.sh"));
+
+    env.insert("STACKCLASS_TIMEOUT_SECONDS".into(), OsString::from_vec(b"1\xff".to_vec()));
+    let err = Context::from_env_os(env, &echo_definition()).unwrap_err();
+    assert!(matches!(err, TesterError::InvalidConfig(_)), "{err:?}");
+    assert!(err.to_string().contains("STACKCLASS_TIMEOUT_SECONDS"), "{err}");
+}
@@ -0,0 +1,146 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use tester::{Case, CaseError, Definition, Harness, TesterError};
+
+fn pass_func(_harness: &Harness) -> Result<(), CaseError> {
+    Ok(())
+}
+
+#[test]
+fn test_unique_slugs() {
+    let definition = Definition {
+        cases: vec![
+            Case::new("test-1", Arc::new(pass_func)),
+            Case::new("test-2", Arc::new(pass_func)),
+        ],
+        anti_cheat_cases: vec![Case::new("anti-cheat-1", Arc::new(pass_func))],
+        ..Default::default()
+    };
+
+    assert!(definition.check_unique_slugs().is_ok());
+}
+
+#[test]
+fn test_duplicate_slugs() {
+    let definition = Definition {
+        cases: vec![
+            Case::new("test-1", Arc::new(pass_func)),
+            Case::new("test-1", Arc::new(pass_func)),
+        ],
+        ..Default::default()
+    };
+
+    let err = definition.check_unique_slugs().unwrap_err();
+    assert!(matches!(err, TesterError::InvalidTestCase(msg) if msg.contains("test-1")));
+}
+
+#[test]
+fn test_duplicate_slugs_across_anti_cheat_cases() {
+    let definition = Definition {
+        cases: vec![Case::new("test-1", Arc::new(pass_func))],
+        anti_cheat_cases: vec![Case::new("test-1", Arc::new(pass_func))],
+        ..Default::default()
+    };
+
+    let err = definition.check_unique_slugs().unwrap_err();
+    assert!(matches!(err, TesterError::InvalidTestCase(msg) if msg.contains("test-1")));
+}
+
+#[test]
+fn test_add_cases() {
+    let mut definition = Definition::default();
+    definition.add_case(Case::new("test-1", Arc::new(pass_func))).unwrap();
+    definition.add_case(Case::new("test-2", Arc::new(pass_func))).unwrap();
+    definition.add_anti_cheat_case(Case::new("anti-cheat-1", Arc::new(pass_func))).unwrap();
+
+    let slugs: Vec<_> = definition.cases.iter().map(|case| case.slug.as_str()).collect();
+    assert_eq!(slugs, ["test-1", "test-2"]);
+    assert!(definition.find_anti_cheat_case("anti-cheat-1").is_some());
+}
+
+#[test]
+fn test_add_duplicate_case() {
+    let mut definition = Definition::default();
+    definition.add_case(Case::new("test-1", Arc::new(pass_func))).unwrap();
+    definition.add_anti_cheat_case(Case::new("anti-cheat-1", Arc::new(pass_func))).unwrap();
+
+    let err = definition.add_case(Case::new("test-1", Arc::new(pass_func))).unwrap_err();
+    assert!(matches!(err, TesterError::InvalidTestCase(msg) if msg.contains("test-1")));
+
+    let err = definition.add_anti_cheat_case(Case::new("test-1", Arc::new(pass_func))).unwrap_err();
+    assert!(matches!(err, TesterError::InvalidTestCase(msg) if msg.contains("test-1")));
+
+    let err = definition.add_case(Case::new("anti-cheat-1", Arc::new(pass_func))).unwrap_err();
+    assert!(matches!(err, TesterError::InvalidTestCase(msg) if msg.contains("anti-cheat-1")));
+
+    assert_eq!(definition.cases.len(), 1);
+    assert_eq!(definition.anti_cheat_cases.len(), 1);
+}
+
+#[test]
+fn test_builder() {
+    let definition = Definition::builder()
+        .executable_name("your_program.sh")
+        .legacy_executable_name("legacy_program.sh")
+        .case(Case::new("test-1", Arc::new(pass_func)))
+        .cases([Case::new("test-2", Arc::new(pass_func)), Case::new("test-3", Arc::new(pass_func))])
+        .anti_cheat_case(Case::new("anti-cheat-1", Arc::new(pass_func)))
+        .build()
+        .unwrap();
+
+    assert_eq!(definition.executable_name, "your_program.sh");
+    assert_eq!(definition.legacy_executable_name.as_deref(), Some("legacy_program.sh"));
+    assert_eq!(definition.cases.len(), 3);
+    assert!(definition.find_case("test-2").is_some());
+    assert!(definition.find_anti_cheat_case("anti-cheat-1").is_some());
+}
+
+#[test]
+fn test_builder_requires_executable_name() {
+    let err =
+        Definition::builder().case(Case::new("test-1", Arc::new(pass_func))).build().unwrap_err();
+    assert!(matches!(err, TesterError::InvalidDefinition(_)));
+}
+
+#[test]
+fn test_validate() {
+    let definition = || Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![Case::new("test-1", Arc::new(pass_func))],
+        anti_cheat_cases: vec![Case::new("anti-cheat-1", Arc::new(pass_func))],
+        ..Default::default()
+    };
+    assert!(definition().validate().is_ok());
+
+    // Slugs carry no meaning beyond identifying a case.
+    let mut definition = definition();
+    definition.cases.push(Case::new("anti-cheat-2", Arc::new(pass_func)));
+    assert!(definition.validate().is_ok());
+
+    definition.cases.push(Case::new("", Arc::new(pass_func)));
+    let err = definition.validate().unwrap_err();
+    assert!(matches!(err, TesterError::InvalidTestCase(msg) if msg.contains("empty")));
+
+    definition.cases.pop();
+    definition.anti_cheat_cases.push(Case::new("test-1", Arc::new(pass_func)));
+    let err = definition.validate().unwrap_err();
+    assert!(matches!(err, TesterError::InvalidTestCase(msg) if msg.contains("test-1")));
+
+    definition.executable_name.clear();
+    let err = definition.validate().unwrap_err();
+    assert!(matches!(err, TesterError::InvalidDefinition(_)));
+}
@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use std::path::PathBuf;
-use tester::{Executable, TesterError};
+use tester::{Executable, ExitReason, TesterError};
 
 #[cfg(unix)]
 #[test]
@@ -59,6 +59,26 @@ fn test_output_capture() {
     assert_eq!(stderr, b"test\n");
 }
 
+#[cfg(unix)]
+#[test]
+fn test_owned_args() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/echo.sh");
+    let port: u16 = 4021;
+    let args = vec!["--port".to_string(), port.to_string()];
+
+    let mut exe = Executable::new(path).unwrap();
+    let (borrowed, _, _) = exe.run(&["--port", "4021"]).unwrap();
+    let (owned, _, _) = exe.run_owned(&args).unwrap();
+    assert_eq!(borrowed, b"--port 4021\n");
+    assert_eq!(owned, borrowed);
+
+    exe.start_owned(&args).unwrap();
+    let line = exe.read_until(b"\n", Duration::from_secs(5)).unwrap();
+    assert_eq!(line, b"--port 4021\n");
+}
+
 #[cfg(unix)]
 #[test]
 fn test_exit_code() {
@@ -72,6 +92,17 @@ fn test_exit_code() {
     assert!(!status.success());
 }
 
+#[cfg(windows)]
+#[test]
+fn test_rejects_non_executable_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("notes.txt");
+    std::fs::write(&path, "not a program").unwrap();
+
+    let err = Executable::new(path).unwrap_err();
+    assert!(matches!(err, TesterError::ProcessExecution(_)), "{err:?}");
+}
+
 #[cfg(windows)]
 #[test]
 fn test_exit_code() {
@@ -105,7 +136,39 @@ fn test_timeout() {
 
     exe.start(&[]).unwrap();
     let err = exe.wait().unwrap_err();
-    assert!(matches!(err, TesterError::WaitTimeout(_)));
+    assert!(matches!(err, TesterError::WaitTimeoutWithOutput { .. }));
+    assert!(err.is_timeout());
+}
+
+#[test]
+fn test_timeout_kill_failed_error() {
+    use std::time::Duration;
+
+    // Killing our own children cannot fail in a test, so check what `wait` reports if it does.
+    let err = TesterError::WaitTimeoutKillFailed {
+        timeout: Duration::from_secs(1),
+        reason: "Operation not permitted (os error 1)".to_string(),
+    };
+    assert!(err.is_timeout());
+    assert_eq!(
+        err.to_string(),
+        "Process wait timed out after 1s, and killing the process failed: Operation not \
+         permitted (os error 1)"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_timeout_keeps_partial_output() {
+    use std::time::Duration;
+    let path = PathBuf::from("tests/bin/ticker.sh");
+    let mut exe = Executable::new(path).unwrap().with_timeout(Duration::from_millis(500));
+
+    exe.start(&[]).unwrap();
+    match exe.wait().unwrap_err() {
+        TesterError::WaitTimeoutWithOutput { stdout, .. } => assert_eq!(stdout, b"one\n"),
+        err => panic!("unexpected error: {err:?}"),
+    }
 }
 
 #[cfg(unix)]
@@ -119,3 +182,777 @@ fn test_kill_after_timeout() {
     let _ = exe.wait();
     assert!(!exe.is_running());
 }
+
+#[cfg(unix)]
+#[test]
+fn test_with_env() {
+    use std::collections::HashMap;
+
+    let path = PathBuf::from("tests/bin/env.sh");
+    let vars = HashMap::from([("GREETING".to_string(), "hello".to_string())]);
+    let mut exe = Executable::new(path).unwrap().with_env(vars);
+    exe.env("TARGET", "world");
+
+    exe.start(&["GREETING", "TARGET"]).unwrap();
+    let (stdout, _, _) = exe.wait().unwrap();
+    assert_eq!(stdout, b"GREETING=hello\nTARGET=world\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_with_clear_env() {
+    let path = PathBuf::from("tests/bin/env.sh");
+    let mut exe = Executable::new(path).unwrap().with_clear_env(true);
+    exe.env("GREETING", "hello");
+
+    exe.start(&["GREETING", "HOME"]).unwrap();
+    let (stdout, _, _) = exe.wait().unwrap();
+    assert_eq!(stdout, b"GREETING=hello\nHOME=\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_write_line() {
+    let path = PathBuf::from("tests/bin/readlines.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&[]).unwrap();
+    exe.write_line("first").unwrap();
+    exe.write_line("second").unwrap();
+
+    let (stdout, _, status) = exe.wait().unwrap();
+    assert!(status.success());
+    assert_eq!(stdout, b"first\nsecond\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_close_stdin() {
+    let path = PathBuf::from("tests/bin/readlines.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&[]).unwrap();
+    exe.write_line("only").unwrap();
+    exe.close_stdin().unwrap();
+
+    let (stdout, _, _) = exe.wait().unwrap();
+    assert_eq!(stdout, b"only\n\n");
+
+    let err = exe.write_line("late").unwrap_err();
+    assert!(matches!(err, TesterError::NoProcessRunning));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_close_stdin_lets_process_finish() {
+    let path = PathBuf::from("tests/bin/cat.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&[]).unwrap();
+    for line in ["one", "two", "three"] {
+        exe.write_line(line).unwrap();
+    }
+    exe.close_stdin().unwrap();
+
+    let err = exe.write_line("four").unwrap_err();
+    assert!(matches!(err, TesterError::StdinCaptureFailed));
+
+    let (stdout, _, status) = exe.wait().unwrap();
+    assert!(status.success());
+    assert_eq!(stdout, b"one\ntwo\nthree\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_max_output_bytes() {
+    use std::time::{Duration, Instant};
+
+    let path = PathBuf::from("tests/bin/yes.sh");
+    let mut exe = Executable::new(path).unwrap().with_max_output_bytes(1000);
+
+    let start = Instant::now();
+    exe.start(&[]).unwrap();
+    let (stdout, _, status) = exe.wait().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    assert!(exe.output_truncated());
+    assert_eq!(stdout, b"y\n".repeat(500));
+    assert!(!status.success(), "the process was killed");
+    assert!(!exe.is_running());
+
+    let mut exe =
+        Executable::new(PathBuf::from("tests/bin/echo.sh")).unwrap().with_max_output_bytes(1000);
+    exe.start(&["hello"]).unwrap();
+    assert_eq!(exe.wait().unwrap().0, b"hello\n");
+    assert!(!exe.output_truncated());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_max_output_bytes() {
+    use std::time::{Duration, Instant};
+
+    let path = PathBuf::from("tests/bin/yes.sh");
+    let mut exe = Executable::new(path).unwrap().with_max_output_bytes(1000);
+
+    let start = Instant::now();
+    let (stdout, _, status) = exe.run(&[]).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    assert!(exe.output_truncated());
+    assert_eq!(stdout, b"y\n".repeat(500));
+    assert!(!status.success(), "the process was killed");
+
+    let (stdout, _, _) = exe.run_with_stdin(b"", &[]).unwrap();
+    assert_eq!(stdout, b"y\n".repeat(500));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_disabled_capture() {
+    let path = PathBuf::from("tests/bin/alternate.sh");
+    let mut exe = Executable::new(path).unwrap().with_capture(false, true);
+
+    exe.start(&[]).unwrap();
+    let (stdout, stderr, status) = exe.wait().unwrap();
+    assert!(status.success());
+    assert!(stdout.is_empty());
+    assert_eq!(stderr, b"err1\nerr2\n");
+
+    let mut exe = exe.with_capture(true, false);
+    let (stdout, stderr, _) = exe.run_text("", &[]).unwrap();
+    assert_eq!(stdout, "out1\nout2\ndone\n");
+    assert!(stderr.is_empty());
+
+    let (stdout, stderr, _) = exe.with_capture(false, false).run(&[]).unwrap();
+    assert!(stdout.is_empty());
+    assert!(stderr.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_restart() {
+    use std::time::Duration;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = PathBuf::from("tests/bin/persist.sh").canonicalize().unwrap();
+    let mut exe = Executable::new(path).unwrap().with_working_dir(dir.path().to_path_buf());
+
+    exe.start(&["alpha"]).unwrap();
+    exe.read_until(b"saved alpha\n", Duration::from_secs(5)).unwrap();
+
+    exe.restart(&["beta"]).unwrap();
+    let stdout = exe.read_until(b"\n", Duration::from_secs(5)).unwrap();
+    assert_eq!(stdout, b"recovered alpha\n");
+    assert!(exe.is_running());
+
+    exe.kill().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_interact() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/increment.sh");
+    let mut exe = Executable::new(path).unwrap();
+    exe.start(&[]).unwrap();
+
+    {
+        let (mut stdin, mut stdout) = exe.interact().unwrap();
+        let mut n = 0;
+        for _ in 0..3 {
+            stdin.write_line(&n.to_string()).unwrap();
+            n = stdout.read_line(Duration::from_secs(5)).unwrap().trim().parse().unwrap();
+        }
+        assert_eq!(n, 3);
+    }
+
+    let err = exe.write_line("3").unwrap_err();
+    assert!(matches!(err, TesterError::StdinCaptureFailed));
+
+    // The writer closed stdin on drop, so the program finishes.
+    let (stdout, _, status) = exe.wait().unwrap();
+    assert!(status.success());
+    assert_eq!(stdout, b"1\n2\n3\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_exit_status_and_output_kept_after_wait() {
+    let path = PathBuf::from("tests/bin/crash.sh");
+    let mut exe = Executable::new(path).unwrap();
+    assert_eq!(exe.exit_status(), None);
+
+    exe.start(&[]).unwrap();
+    let (_, stderr, status) = exe.wait().unwrap();
+
+    assert_eq!(exe.exit_status(), Some(status));
+    assert_eq!(exe.exit_status().unwrap().code(), Some(3));
+    assert_eq!(exe.captured_stderr(), stderr);
+    assert!(exe.last_stderr().ends_with(b"panic: boom\n"));
+
+    exe.start(&[]).unwrap();
+    assert_eq!(exe.exit_status(), None);
+    exe.kill().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_stdout_utf8() {
+    let mut exe = Executable::new(PathBuf::from("tests/bin/invalid_utf8.sh")).unwrap();
+    exe.start(&[]).unwrap();
+    exe.wait().unwrap();
+
+    let err = exe.stdout_utf8().unwrap_err().to_string();
+    assert!(err.contains("invalid byte 0xff at byte offset 5 (0x5)"), "{err}");
+
+    let mut exe = Executable::new(PathBuf::from("tests/bin/echo.sh")).unwrap();
+    exe.start(&["héllo"]).unwrap();
+    exe.wait().unwrap();
+    assert_eq!(exe.stdout_utf8().unwrap(), "héllo\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_read_available_stdout() {
+    use std::{thread, time::Duration};
+
+    let path = PathBuf::from("tests/bin/ticker.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&[]).unwrap();
+    thread::sleep(Duration::from_millis(300));
+    assert_eq!(exe.read_available_stdout().unwrap(), b"one\n");
+    assert!(exe.is_running());
+
+    let (stdout, _, _) = exe.wait().unwrap();
+    assert_eq!(stdout, b"one\ntwo\n");
+    assert_eq!(exe.read_available_stdout().unwrap(), b"two\n");
+    assert!(exe.read_available_stdout().unwrap().is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_read_until() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/banner.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&[]).unwrap();
+    let read = exe.read_until(b"Listening", Duration::from_secs(5)).unwrap();
+    assert_eq!(read, b"starting\nListening");
+
+    let read = exe.read_until(b"ready\n", Duration::from_secs(5)).unwrap();
+    assert_eq!(read, b" on 1234\nready\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_read_until_timeout() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/sleep.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&[]).unwrap();
+    let err = exe.read_until(b"never", Duration::from_millis(200)).unwrap_err();
+    assert!(matches!(err, TesterError::WaitTimeout(_)));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_read_until_output_closed() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/echo.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&["hello"]).unwrap();
+    let err = exe.read_until(b"never", Duration::from_secs(5)).unwrap_err();
+    assert!(matches!(err, TesterError::OutputClosed));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shutdown_graceful() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/trap_term.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&[]).unwrap();
+    exe.read_until(b"ready\n", Duration::from_secs(5)).unwrap();
+
+    let status = exe.shutdown(Duration::from_secs(5)).unwrap();
+    assert!(status.success());
+    assert!(!exe.is_running());
+    assert_eq!(exe.read_until(b"terminated\n", Duration::from_secs(5)).unwrap(), b"terminated\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_drop_grace() {
+    use std::time::Duration;
+
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("terminated");
+    let marker_arg = marker.to_str().unwrap();
+    let path = PathBuf::from("tests/bin/trap_term_file.sh");
+
+    let mut exe = Executable::new(path.clone()).unwrap();
+    exe.start(&[marker_arg]).unwrap();
+    exe.read_until(b"ready\n", Duration::from_secs(5)).unwrap();
+    drop(exe);
+    assert!(!marker.exists());
+
+    let mut exe = Executable::new(path).unwrap().with_drop_grace(Duration::from_secs(5));
+    exe.start(&[marker_arg]).unwrap();
+    exe.read_until(b"ready\n", Duration::from_secs(5)).unwrap();
+    drop(exe);
+    assert_eq!(std::fs::read_to_string(&marker).unwrap(), "terminated\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shutdown_escalates_to_kill() {
+    use std::{os::unix::process::ExitStatusExt, time::Duration};
+
+    let path = PathBuf::from("tests/bin/ignore_term.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&[]).unwrap();
+    exe.read_until(b"ready\n", Duration::from_secs(5)).unwrap();
+
+    let status = exe.shutdown(Duration::from_millis(200)).unwrap();
+    assert_eq!(status.signal(), Some(9));
+    assert!(!exe.is_running());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_poll_interval() {
+    use std::time::{Duration, Instant};
+
+    let path = PathBuf::from("tests/bin/exit.sh");
+    let mut exe = Executable::new(path).unwrap().with_poll_interval(Duration::from_millis(5));
+
+    let start = Instant::now();
+    exe.start(&["0"]).unwrap();
+    let (_, _, status) = exe.wait().unwrap();
+
+    assert!(status.success());
+    assert!(start.elapsed() < Duration::from_millis(100));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pid() {
+    let path = PathBuf::from("tests/bin/sleep.sh");
+    let mut exe = Executable::new(path).unwrap();
+    assert_eq!(exe.pid(), None);
+
+    exe.start(&[]).unwrap();
+    let pid = exe.pid().unwrap();
+    // Signal 0 only checks that the process exists.
+    assert_eq!(unsafe { libc::kill(pid as libc::pid_t, 0) }, 0);
+
+    exe.kill().unwrap();
+    assert_eq!(exe.pid(), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_kill_process_group() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/spawn_child.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&[]).unwrap();
+    exe.read_until(b"started\n", Duration::from_secs(5)).unwrap();
+    exe.kill().unwrap();
+
+    // The backgrounded child shares the stdout pipe, so the output only closes once it is gone too.
+    let err = exe.read_until(b"never", Duration::from_secs(5)).unwrap_err();
+    assert!(matches!(err, TesterError::OutputClosed));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_memory_limit() {
+    let path = PathBuf::from("tests/bin/allocate_buffer.sh");
+    let mut exe = Executable::new(path).unwrap().with_memory_limit(100 * 1024 * 1024);
+
+    exe.start(&[]).unwrap();
+    let err = exe.wait().unwrap_err();
+    assert!(matches!(err, TesterError::ResourceLimitExceeded(_)), "{err:?}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_crash_under_resource_limits_is_not_a_breach() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/segfault.sh");
+    let mut exe = Executable::new(path)
+        .unwrap()
+        .with_memory_limit(1024 * 1024 * 1024)
+        .with_cpu_time_limit(Duration::from_secs(5));
+    exe.start(&[]).unwrap();
+    let (_, _, reason) = exe.wait_with_reason().unwrap();
+    assert_eq!(reason, ExitReason::Signaled(libc::SIGSEGV));
+
+    let path = PathBuf::from("tests/bin/sleep.sh");
+    let mut exe = Executable::new(path)
+        .unwrap()
+        .with_memory_limit(1024 * 1024 * 1024)
+        .with_cpu_time_limit(Duration::from_secs(5));
+    exe.start(&[]).unwrap();
+    exe.send_signal(libc::SIGKILL).unwrap();
+    let (_, _, reason) = exe.wait_with_reason().unwrap();
+    assert_eq!(reason, ExitReason::Signaled(libc::SIGKILL));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_last_max_rss() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/allocate.sh");
+    let mut exe = Executable::new(path).unwrap().with_timeout(Duration::from_secs(60));
+    assert_eq!(exe.last_max_rss(), None);
+
+    exe.start(&[]).unwrap();
+    let (_, _, status) = exe.wait().unwrap();
+    assert!(status.success());
+
+    // The shell holds the 200 MB string it built in memory.
+    let max_rss = exe.last_max_rss().unwrap();
+    assert!((150_000_000..2_000_000_000).contains(&max_rss), "{max_rss}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_status_and_max_rss_after_polling() {
+    use std::{thread, time::Duration};
+
+    let path = PathBuf::from("tests/bin/exit.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    // Polling reaps the process, which `wait` must still report on.
+    exe.start(&["3"]).unwrap();
+    while exe.is_running() {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let (_, _, status) = exe.wait().unwrap();
+    assert_eq!(status.code(), Some(3));
+    assert!(exe.last_max_rss().is_some());
+    assert!(exe.kill().is_ok());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cpu_time_limit() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/spin.sh");
+    let mut exe = Executable::new(path).unwrap().with_cpu_time_limit(Duration::from_secs(1));
+
+    exe.start(&[]).unwrap();
+    let err = exe.wait().unwrap_err();
+    assert!(matches!(err, TesterError::ResourceLimitExceeded(_)), "{err:?}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_wait_with_reason() {
+    use std::time::Duration;
+
+    let mut exe = Executable::new(PathBuf::from("tests/bin/exit.sh")).unwrap();
+    exe.start(&["3"]).unwrap();
+    let (_, _, reason) = exe.wait_with_reason().unwrap();
+    assert_eq!(reason, ExitReason::Exited(3));
+
+    let mut exe = Executable::new(PathBuf::from("tests/bin/segfault.sh")).unwrap();
+    exe.start(&[]).unwrap();
+    let (_, _, reason) = exe.wait_with_reason().unwrap();
+    assert_eq!(reason, ExitReason::Signaled(libc::SIGSEGV));
+
+    let mut exe = Executable::new(PathBuf::from("tests/bin/sleep.sh"))
+        .unwrap()
+        .with_timeout(Duration::from_millis(100));
+    exe.start(&[]).unwrap();
+    let (_, _, reason) = exe.wait_with_reason().unwrap();
+    assert_eq!(reason, ExitReason::TimedOut);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_combined_output() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/alternate.sh");
+    let mut exe = Executable::new(path).unwrap().with_combined_output(true);
+
+    exe.start(&[]).unwrap();
+    exe.read_until(b"done\n", Duration::from_secs(5)).unwrap();
+
+    let combined = exe.combined_output();
+    let expected = vec![
+        (true, b"out1\n".to_vec()),
+        (false, b"err1\n".to_vec()),
+        (true, b"out2\n".to_vec()),
+        (false, b"err2\n".to_vec()),
+        (true, b"done\n".to_vec()),
+    ];
+    assert_eq!(combined, expected);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_capture_buffer_drops_unread_output() {
+    use std::{thread, time::Duration};
+
+    let path = PathBuf::from("tests/bin/flood.sh");
+    let mut exe = Executable::new(path).unwrap().with_capture_buffer(16 * 1024);
+
+    exe.start(&[]).unwrap();
+
+    // Nothing is collecting the output, yet the program never blocks on a full pipe.
+    thread::sleep(Duration::from_millis(500));
+    assert!(!exe.is_running());
+
+    let stdout = exe.read_available_stdout().unwrap();
+    assert!(stdout.len() <= 16 * 1024, "{}", stdout.len());
+    assert!(stdout.iter().all(|&byte| byte == 0));
+    assert!(exe.output_truncated());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_wait_collects_output_within_capture_buffer() {
+    let path = PathBuf::from("tests/bin/flood.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&[]).unwrap();
+    let (stdout, _, status) = exe.wait().unwrap();
+    assert!(status.success());
+    assert_eq!(stdout.len(), 4_000_000);
+    assert!(!exe.output_truncated());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_with_stdin() {
+    let path = PathBuf::from("tests/bin/cat.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    let input = b"line\n".repeat(100_000);
+    let (stdout, _, status) = exe.run_with_stdin(&input, &[]).unwrap();
+    assert!(status.success());
+    assert_eq!(stdout, input);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_stdin_writes_time_out() {
+    use std::time::{Duration, Instant};
+
+    let path = PathBuf::from("tests/bin/sleep.sh");
+    let mut exe = Executable::new(path).unwrap().with_timeout(Duration::from_millis(500));
+    let input = vec![b'x'; 4 * 1024 * 1024];
+
+    let start = Instant::now();
+    let err = exe.run_with_stdin(&input, &[]).unwrap_err();
+    assert!(matches!(err, TesterError::WaitTimeout(_)), "{err:?}");
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // Input that fits in the pipe is written right away, so the timeout is an ordinary one.
+    let err = exe.run_with_stdin(b"small", &[]).unwrap_err();
+    assert!(matches!(err, TesterError::WaitTimeoutWithOutput { .. }), "{err:?}");
+
+    exe.start(&[]).unwrap();
+    let start = Instant::now();
+    let err = exe.write_stdin(&input).unwrap_err();
+    assert!(matches!(err, TesterError::WaitTimeout(_)), "{err:?}");
+    assert!(start.elapsed() < Duration::from_secs(5));
+    exe.kill().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_reuse_across_runs() {
+    let path = PathBuf::from("tests/bin/echo.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    for iteration in 0..10 {
+        let arg = format!("iteration {iteration}");
+        exe.start(&[&arg]).unwrap();
+        let (stdout, stderr, status) = exe.wait().unwrap();
+        assert!(status.success());
+        assert_eq!(stdout, format!("{arg}\n").as_bytes());
+        assert!(stderr.is_empty());
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_read_exact_stdout() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/frames.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&[]).unwrap();
+    assert_eq!(exe.read_exact_stdout(4, Duration::from_secs(5)).unwrap(), [1, 2, 3, 4]);
+    assert_eq!(exe.read_exact_stdout(4, Duration::from_secs(5)).unwrap(), [5, 6, 7, 8]);
+
+    let err = exe.read_exact_stdout(1, Duration::from_secs(5)).unwrap_err();
+    assert!(matches!(err, TesterError::OutputClosed), "{err:?}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_text() {
+    let path = PathBuf::from("tests/bin/cat.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    let input = "PING\nECHO héllo\nQUIT\n";
+    let (stdout, stderr, status) = exe.run_text(input, &[]).unwrap();
+    assert!(status.success());
+    assert_eq!(stdout, input);
+    assert_eq!(stderr, "");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_with_stdin_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let fixture = dir.path().join("input.txt");
+    let input = b"fixture\n".repeat(100_000);
+    std::fs::write(&fixture, &input).unwrap();
+
+    let mut exe = Executable::new(PathBuf::from("tests/bin/cat.sh")).unwrap();
+    let (stdout, _, status) = exe.run_with_stdin_file(&fixture, &[]).unwrap();
+    assert!(status.success());
+    assert_eq!(stdout, input);
+
+    let err = exe.run_with_stdin_file(&dir.path().join("missing.txt"), &[]).unwrap_err();
+    assert!(matches!(err, TesterError::Io(_)));
+    assert!(err.to_string().contains("missing.txt"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_send_signal() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/trap_usr1.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    let err = exe.send_signal(libc::SIGUSR1).unwrap_err();
+    assert!(matches!(err, TesterError::NoProcessRunning));
+
+    exe.start(&[]).unwrap();
+    exe.read_until(b"ready\n", Duration::from_secs(5)).unwrap();
+
+    exe.send_signal(libc::SIGUSR1).unwrap();
+    exe.read_until(b"stats\n", Duration::from_secs(5)).unwrap();
+    assert!(exe.is_running());
+
+    exe.kill().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_start_reports_lost_permissions() {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("program.sh");
+    fs::write(&path, "#!/bin/sh\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut exe = Executable::new(path.clone()).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+    let err = exe.start(&[]).unwrap_err();
+    assert!(matches!(err, TesterError::ExecutableNotExecutable(_)), "{err:?}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_start_reports_missing_interpreter() {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("program.sh");
+    fs::write(&path, "#!/nonexistent/interpreter\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut exe = Executable::new(path).unwrap();
+    let err = exe.start(&[]).unwrap_err();
+    assert!(matches!(err, TesterError::InterpreterNotFound(_)), "{err:?}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_wait_collects_output_of_fast_exit() {
+    use std::time::Duration;
+
+    let path = PathBuf::from("tests/bin/burst.sh");
+    let mut exe = Executable::new(path).unwrap().with_poll_interval(Duration::from_millis(1));
+
+    for _ in 0..50 {
+        exe.start(&[]).unwrap();
+        let (stdout, stderr, status) = exe.wait().unwrap();
+        assert!(status.success());
+        assert_eq!(stdout, b"out");
+        assert_eq!(stderr, b"err");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_wait_success_reports_stderr_tail() {
+    let path = PathBuf::from("tests/bin/crash.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&[]).unwrap();
+    let err = exe.wait_success().unwrap_err();
+    assert!(matches!(err, TesterError::ProcessFailed { .. }), "{err:?}");
+
+    let message = err.to_string();
+    assert!(message.contains("exit status: 3"), "{message}");
+    assert!(message.ends_with("panic: boom"), "{message}");
+    assert!(message.contains("trace line 7\n"), "{message}");
+    assert!(!message.contains("trace line 6\n"), "{message}");
+
+    let tail = String::from_utf8_lossy(exe.last_stderr()).into_owned();
+    assert!(tail.starts_with("trace line 7\n"), "{tail}");
+    assert!(tail.ends_with("panic: boom\n"), "{tail}");
+
+    let mut exe = Executable::new(PathBuf::from("tests/bin/echo.sh")).unwrap();
+    exe.start(&["ok"]).unwrap();
+    assert_eq!(exe.wait_success().unwrap().0, b"ok\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_kill_keeps_captured_output() {
+    use std::{thread, time::Duration};
+
+    let path = PathBuf::from("tests/bin/ticker.sh");
+    let mut exe = Executable::new(path).unwrap();
+
+    exe.start(&[]).unwrap();
+    // Give the program time to print its first line without collecting it.
+    thread::sleep(Duration::from_millis(300));
+    assert!(exe.captured_stdout().is_empty());
+
+    exe.kill().unwrap();
+    assert_eq!(exe.captured_stdout(), b"one\n");
+    assert!(exe.captured_stderr().is_empty());
+}
@@ -0,0 +1,543 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    net::{TcpListener, UdpSocket},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+use tester::{Executable, Harness, TesterError};
+use tracing::Level;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// An in-memory log sink shared between a subscriber and the test.
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl LogBuffer {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogBuffer {
+    type Writer = LogBuffer;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+fn harness() -> Harness {
+    Harness::new(Executable::new(PathBuf::from("examples/echo-tester/your_program.sh")).unwrap())
+}
+
+#[test]
+fn test_connect_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+
+    let stream = harness().connect_tcp(&addr, 3, Duration::from_millis(10)).unwrap();
+    assert_eq!(stream.peer_addr().unwrap().to_string(), addr);
+}
+
+#[test]
+fn test_connect_tcp_exhausts_retries() {
+    let addr = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    };
+
+    let err = harness().connect_tcp(&addr, 2, Duration::from_millis(10)).unwrap_err();
+    assert!(matches!(err, TesterError::Io(_)));
+    assert!(err.to_string().contains("after 3 attempts"));
+}
+
+#[test]
+fn test_retry_succeeds_on_third_attempt() {
+    let mut calls = 0;
+    let value = harness()
+        .retry(5, Duration::from_millis(1), || {
+            calls += 1;
+            if calls < 3 { Err(TesterError::assertion("not ready").into()) } else { Ok(calls) }
+        })
+        .unwrap();
+    assert_eq!(value, 3);
+    assert_eq!(calls, 3);
+}
+
+#[test]
+fn test_retry_returns_last_error() {
+    let mut calls = 0;
+    let err = harness()
+        .retry_with_backoff(3, Duration::from_millis(1), || -> Result<(), _> {
+            calls += 1;
+            Err(TesterError::assertion(format!("attempt {calls}")).into())
+        })
+        .unwrap_err();
+    assert_eq!(calls, 3);
+    assert!(err.to_string().contains("attempt 3"));
+}
+
+#[test]
+fn test_wait_until() {
+    let start = Instant::now();
+    harness()
+        .wait_until(
+            || start.elapsed() >= Duration::from_millis(100),
+            Duration::from_secs(5),
+            Duration::from_millis(10),
+        )
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let err = harness()
+        .wait_until(|| false, Duration::from_millis(50), Duration::from_millis(10))
+        .unwrap_err();
+    assert!(err.to_string().contains("not met within"), "{err}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_assert_running_for() {
+    let mut exe = Executable::new(PathBuf::from("tests/bin/sleep.sh")).unwrap();
+    exe.start(&[]).unwrap();
+    harness().assert_running_for(&mut exe, Duration::from_millis(200)).unwrap();
+    exe.kill().unwrap();
+
+    let mut exe = Executable::new(PathBuf::from("tests/bin/exit_after_1s.sh")).unwrap();
+    exe.start(&[]).unwrap();
+    let start = Instant::now();
+    let err = harness().assert_running_for(&mut exe, Duration::from_secs(3)).unwrap_err();
+    assert!(start.elapsed() < Duration::from_secs(3));
+
+    let message = err.to_string();
+    assert!(message.contains("expected it to keep running for 3s"), "{message}");
+    assert!(message.contains("exit status: 3"), "{message}");
+    assert!(message.ends_with("stderr:\npanic: idle connection"), "{message}");
+}
+
+#[test]
+fn test_spawn_helper_is_joined_on_teardown() {
+    let harness = harness();
+    let served = Arc::new(Mutex::new(0));
+
+    let counter = served.clone();
+    let helper = harness.spawn_helper(move |handle| {
+        while !handle.is_stopped() {
+            *counter.lock().unwrap() += 1;
+            thread::sleep(Duration::from_millis(10));
+        }
+    });
+
+    thread::sleep(Duration::from_millis(50));
+    assert!(!helper.is_stopped());
+    assert!(!helper.is_finished());
+
+    harness.run_teardown_funcs();
+    assert!(helper.is_stopped());
+    assert!(helper.is_finished());
+
+    // The helper no longer runs once teardown has returned.
+    let count = *served.lock().unwrap();
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(*served.lock().unwrap(), count);
+}
+
+#[test]
+fn test_teardown_timeout_abandons_hanging_func() {
+    let buffer = LogBuffer::default();
+    let subscriber =
+        tracing_subscriber::fmt().with_writer(buffer.clone()).with_ansi(false).finish();
+
+    let harness = harness().with_log_prefix("stage-1");
+    let finished = Arc::new(Mutex::new(false));
+    let flag = finished.clone();
+    harness.register_teardown_func(move || *flag.lock().unwrap() = true);
+    harness.register_teardown_func(|| thread::sleep(Duration::from_secs(60)));
+
+    let start = Instant::now();
+    tracing::subscriber::with_default(subscriber, || {
+        harness.run_teardown_funcs_with_timeout(Duration::from_millis(100));
+    });
+
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(*finished.lock().unwrap(), "the remaining teardown functions still run");
+    assert!(buffer.contents().contains("[stage-1] teardown function did not finish within 100ms"));
+}
+
+#[test]
+fn test_logger_prefix() {
+    let buffer = LogBuffer::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(buffer.clone())
+        .with_max_level(Level::DEBUG)
+        .with_ansi(false)
+        .finish();
+
+    let harness = harness().with_log_prefix("stage-1");
+    tracing::subscriber::with_default(subscriber, || {
+        let logger = harness.logger();
+        logger.infof("server started");
+        logger.debugf(format_args!("sent {} bytes", 4));
+        logger.errorf("unexpected response");
+    });
+
+    let logs = buffer.contents();
+    assert!(logs.contains("[stage-1] server started"));
+    assert!(logs.contains("[stage-1] sent 4 bytes"));
+    assert!(logs.contains("[stage-1] unexpected response"));
+}
+
+#[test]
+fn test_assert_stdout_eq() {
+    let harness = harness();
+    assert!(harness.assert_stdout_eq(b"+PONG\r\n", b"+PONG\r\n").is_ok());
+
+    let expected = b"*1\r\n$4\r\nPING\r\n*1\r\n$4\r\nPING\r\n";
+    let actual = b"*1\r\n$4\r\nPING\r\n*1\r\n$4\r\nPONG\r\n";
+    let err = harness.assert_stdout_eq(expected, actual).unwrap_err().to_string();
+
+    assert!(err.contains("byte offset 23 (0x17)"), "{err}");
+    assert!(err.contains("00000010  0d 0a 24 34 0d 0a 50 49 4e 47"), "{err}");
+    assert!(err.contains("0d 0a 24 34 0d 0a 50 4f 4e 47"), "{err}");
+}
+
+#[test]
+fn test_assert_stdout_eq_length_mismatch() {
+    let err = harness().assert_stdout_eq(b"abc", b"abcd").unwrap_err().to_string();
+    assert!(err.contains("byte offset 3 (0x3): expected 3 bytes, got 4 bytes"), "{err}");
+}
+
+#[test]
+fn test_assert_stdout_eq_normalized() {
+    let harness = harness();
+    assert!(harness.assert_stdout_eq_normalized("+PONG\n", b"+PONG\r\n").is_ok());
+    assert!(harness.assert_stdout_eq(b"+PONG\n", b"+PONG\r\n").is_err());
+
+    let err = harness.assert_stdout_eq_normalized("+PONG\n", b"+PING\r\n").unwrap_err();
+    assert!(err.to_string().contains("byte offset 2 (0x2)"), "{err}");
+}
+
+#[test]
+fn test_assert_json_eq() {
+    let harness = harness();
+    let expected = json!({"id": 1, "tags": ["a", "b"], "owner": {"name": "ada", "admin": true}});
+
+    let reordered = br#"{ "owner": {"admin": true, "name": "ada"},
+        "tags": ["a", "b"], "id": 1 }"#;
+    assert!(harness.assert_json_eq(reordered, expected.clone()).is_ok());
+
+    let err = harness
+        .assert_json_eq(
+            br#"{"id": 1, "tags": ["a", "b"], "owner": {"name": "bob", "admin": true}}"#,
+            expected.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"Assertion failed: JSON mismatch at $.owner.name: expected "ada", got "bob""#
+    );
+
+    let missing = br#"{"id": 1, "tags": ["a", "b"], "owner": {"admin": true}}"#;
+    let err = harness.assert_json_eq(missing, expected.clone()).unwrap_err().to_string();
+    assert!(err.ends_with("at $.owner.name: expected \"ada\", but the key is missing"), "{err}");
+
+    let short = br#"{"id": 1, "tags": ["a"], "owner": {"name": "ada", "admin": true}}"#;
+    let err = harness.assert_json_eq(short, expected.clone()).unwrap_err().to_string();
+    assert!(err.ends_with("at $.tags: expected 2 elements, got 1"), "{err}");
+
+    let err = harness.assert_json_eq(b"not json", expected).unwrap_err().to_string();
+    assert!(err.contains("expected a JSON document, got \"not json\""), "{err}");
+}
+
+#[test]
+fn test_assert_json_eq_compares_numbers_by_value() {
+    let harness = harness();
+
+    assert!(harness.assert_json_eq(b"[1.0, 2, -3.0, 0.5]", json!([1, 2.0, -3, 0.5])).is_ok());
+    assert!(harness.assert_json_eq(b"18446744073709551615", json!(u64::MAX)).is_ok());
+
+    let err = harness.assert_json_eq(b"[1.5]", json!([1])).unwrap_err().to_string();
+    assert!(err.ends_with("at $[0]: expected 1, got 1.5"), "{err}");
+
+    // Integers are compared exactly, even where `f64` would round them to the same value.
+    let err = harness.assert_json_eq(b"9007199254740993", json!(9007199254740992u64));
+    assert!(err.is_err());
+}
+
+#[test]
+fn test_assert_eq_text() {
+    let harness = harness();
+    assert!(harness.assert_eq_text("a\nb\n", "a\nb\n").is_ok());
+
+    let err = harness.assert_eq_text("one\ntwo\nthree\n", "one\n2\nthree\nfour\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Assertion failed: text mismatch (- expected, + actual)\n  one\n- two\n+ 2\n  three\n+ four\n"
+    );
+
+    let err = harness.assert_eq_text("a\n", "a").unwrap_err();
+    assert!(err.to_string().contains("differ only in line endings"));
+}
+
+#[test]
+fn test_udp_roundtrip() {
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap().to_string();
+
+    thread::spawn(move || {
+        let mut buf = [0; 512];
+        let (len, peer) = server.recv_from(&mut buf).unwrap();
+        server.send_to(&buf[..len], peer).unwrap();
+    });
+
+    let response = harness().udp_roundtrip(&addr, b"ping", Duration::from_secs(5)).unwrap();
+    assert_eq!(response, b"ping");
+}
+
+#[test]
+fn test_udp_roundtrip_ignores_other_senders() {
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let stranger = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap().to_string();
+
+    thread::spawn(move || {
+        let mut buf = [0; 512];
+        let (_, peer) = server.recv_from(&mut buf).unwrap();
+        stranger.send_to(b"spoofed", peer).unwrap();
+        server.send_to(b"pong", peer).unwrap();
+    });
+
+    let response = harness().udp_roundtrip(&addr, b"ping", Duration::from_secs(5)).unwrap();
+    assert_eq!(response, b"pong");
+}
+
+#[test]
+fn test_udp_roundtrip_timeout() {
+    // A bound socket that never replies.
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap().to_string();
+
+    let err = harness().udp_roundtrip(&addr, b"ping", Duration::from_millis(100)).unwrap_err();
+    assert!(matches!(err, TesterError::ResponseTimeout(_)));
+}
+
+#[test]
+fn test_reserve_port() {
+    let port = harness().reserve_port().unwrap();
+    assert_ne!(port, 0);
+    assert!(TcpListener::bind(("127.0.0.1", port)).is_ok());
+}
+
+#[test]
+fn test_read_submission_file() {
+    let harness = harness().with_repository_dir("examples/echo-tester");
+
+    let contents = harness.read_submission_file("your_program.sh").unwrap();
+    assert_eq!(contents, std::fs::read("examples/echo-tester/your_program.sh").unwrap());
+    assert_eq!(harness.file_size("your_program.sh").unwrap(), contents.len() as u64);
+
+    let err = harness.read_submission_file("missing.txt").unwrap_err();
+    assert!(matches!(err, TesterError::Io(_)));
+}
+
+#[test]
+fn test_read_submission_file_rejects_traversal() {
+    let submission = harness().with_repository_dir("examples/echo-tester");
+
+    for path in ["../../Cargo.toml", "/etc/passwd", "src/../../../Cargo.toml"] {
+        let err = submission.read_submission_file(path).unwrap_err();
+        assert!(matches!(err, TesterError::InvalidPath(_)), "{path}: {err:?}");
+    }
+
+    let err = harness().file_size("your_program.sh").unwrap_err();
+    assert!(matches!(err, TesterError::InvalidConfig(_)));
+}
+
+#[test]
+fn test_rng_is_seeded_from_env() {
+    use rand::Rng;
+
+    let env = Arc::new(HashMap::from([("STACKCLASS_RANDOM_SEED".to_string(), "7".to_string())]));
+    let first: Vec<u64> = harness().with_env(env.clone()).rng().random_iter().take(8).collect();
+    let second: Vec<u64> = harness().with_env(env).rng().random_iter().take(8).collect();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_rng_replays_from_logged_seed() {
+    use rand::Rng;
+
+    let buffer = LogBuffer::default();
+    let subscriber =
+        tracing_subscriber::fmt().with_writer(buffer.clone()).with_ansi(false).finish();
+
+    let logged = harness().with_log_prefix("stage-1");
+    let (first, second): (Vec<u64>, Vec<u64>) =
+        tracing::subscriber::with_default(subscriber, || {
+            let first = logged.rng().random_iter().take(8).collect();
+            let second = logged.clone().rng().random_iter().take(8).collect();
+            (first, second)
+        });
+    assert_eq!(first, second);
+
+    let logs = buffer.contents();
+    assert_eq!(logs.matches("STACKCLASS_RANDOM_SEED=").count(), 1, "{logs}");
+    let seed: u64 = logs
+        .split("STACKCLASS_RANDOM_SEED=")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or_else(|| panic!("seed not logged: {logs}"));
+
+    let env = Arc::new(HashMap::from([("STACKCLASS_RANDOM_SEED".to_string(), seed.to_string())]));
+    let replay = harness().with_env(env);
+    let replayed: Vec<u64> = replay.rng().random_iter().take(8).collect();
+    assert_eq!(replayed, first);
+    assert_eq!(replay.rng().random_iter().take(8).collect::<Vec<u64>>(), second);
+}
+
+/// Serves a single HTTP request with `response` and returns the server address along with a
+/// handle yielding the raw request.
+fn serve_http_once(response: &'static [u8]) -> (String, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+            let n = stream.read(&mut buf).unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+        stream.write_all(response).unwrap();
+        String::from_utf8(request).unwrap()
+    });
+    (addr, handle)
+}
+
+#[test]
+fn test_http_get() {
+    let (addr, handle) = serve_http_once(
+        b"HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\nContent-Length: 9\r\n\r\nnot found",
+    );
+
+    let response = harness().http_get(&addr, "/missing", Duration::from_secs(5)).unwrap();
+    assert_eq!(response.status, 404);
+    assert_eq!(response.header("content-type"), Some("text/plain"));
+    assert_eq!(response.body, b"not found");
+
+    let request = handle.join().unwrap();
+    assert!(request.starts_with("GET /missing HTTP/1.1\r\n"), "{request}");
+}
+
+#[test]
+fn test_http_get_invalid_response() {
+    let (addr, handle) = serve_http_once(b"SSH-2.0-OpenSSH\r\n\r\n");
+
+    let err = harness().http_get(&addr, "/", Duration::from_secs(5)).unwrap_err();
+    assert!(matches!(err, TesterError::InvalidResponse(_)), "{err:?}");
+    handle.join().unwrap();
+}
+
+#[cfg(feature = "tls")]
+fn serve_tls_echo_once() -> (String, thread::JoinHandle<()>) {
+    use rustls::{
+        ServerConfig, ServerConnection, StreamOwned,
+        pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer},
+    };
+
+    let cert = rcgen::generate_simple_self_signed(["localhost".to_string()]).unwrap();
+    let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der()));
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert.cert.der().clone()], key)
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let handle = thread::spawn(move || {
+        let (tcp, _) = listener.accept().unwrap();
+        let conn = ServerConnection::new(Arc::new(config)).unwrap();
+        let mut stream = StreamOwned::new(conn, tcp);
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).unwrap();
+        stream.write_all(&buf).unwrap();
+        stream.flush().unwrap();
+    });
+
+    (addr, handle)
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn test_connect_tls() {
+    let (addr, handle) = serve_tls_echo_once();
+
+    let mut stream = harness().connect_tls(&addr, "localhost", Duration::from_secs(5)).unwrap();
+    stream.write_all(b"ping").unwrap();
+    let mut buf = [0; 4];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"ping");
+
+    handle.join().unwrap();
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn test_connect_tls_handshake_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+
+    let timeout = Duration::from_millis(200);
+    let result = harness().connect_tls(&addr, "localhost", timeout);
+    assert!(matches!(result, Err(TesterError::ResponseTimeout(t)) if t == timeout));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_spawned_executables_are_killed_on_teardown() {
+    let harness = Harness::new(Executable::new(PathBuf::from("tests/bin/sleep.sh")).unwrap());
+
+    let mut server = harness.spawn_executable();
+    let mut client = harness.spawn_executable();
+    server.start(&[]).unwrap();
+    client.start(&[]).unwrap();
+    assert!(server.is_running());
+    assert!(client.is_running());
+
+    harness.run_teardown_funcs();
+    assert!(!server.is_running());
+    assert!(!client.is_running());
+}
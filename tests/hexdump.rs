@@ -0,0 +1,32 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tester::hexdump;
+
+#[test]
+fn test_hexdump_full_and_partial_rows() {
+    let bytes: Vec<u8> =
+        b"Hello, world!\n\x00\x01".iter().copied().chain([0x7f, 0x80, 0xff, b' ', b'~']).collect();
+
+    assert_eq!(
+        hexdump(&bytes),
+        "00000000  48 65 6c 6c 6f 2c 20 77 6f 72 6c 64 21 0a 00 01  |Hello, world!...|\n\
+         00000010  7f 80 ff 20 7e                                   |... ~           |\n"
+    );
+}
+
+#[test]
+fn test_hexdump_empty() {
+    assert_eq!(hexdump(&[]), "");
+}
@@ -0,0 +1,79 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Installs the global subscriber and redirects this process's stdout, so this test lives in its
+//! own test binary where no other test can interfere.
+
+#![cfg(unix)]
+
+use std::{collections::HashMap, fs, io::Write, os::fd::AsRawFd, process::ExitCode, sync::Arc};
+
+use tester::{Case, CaseError, Definition, Harness, run};
+
+fn pass_func(harness: &Harness) -> Result<(), CaseError> {
+    harness.logger().infof("checking the program");
+    Ok(())
+}
+
+fn fail_func(_harness: &Harness) -> Result<(), CaseError> {
+    Err("fail".to_string().into())
+}
+
+#[test]
+fn test_json_output_is_the_only_stdout() {
+    let cases_json = serde_json::json!([
+        {"slug": "test-1", "log_prefix": "test-1", "title": "Stage #1: test-1"},
+        {"slug": "test-2", "log_prefix": "test-2", "title": "Stage #2: test-2"},
+    ]);
+    let env = HashMap::from([
+        ("STACKCLASS_REPOSITORY_DIR".to_string(), "examples/echo-tester".to_string()),
+        ("STACKCLASS_TEST_CASES_JSON".to_string(), cases_json.to_string()),
+        ("STACKCLASS_OUTPUT_FORMAT".to_string(), "json".to_string()),
+        ("STACKCLASS_TIMING".to_string(), "1".to_string()),
+        ("STACKCLASS_DEBUG".to_string(), "1".to_string()),
+    ]);
+    let definition = Definition {
+        executable_name: "your_program.sh".to_string(),
+        cases: vec![
+            Case::new("test-1", Arc::new(pass_func)),
+            Case::new("test-2", Arc::new(fail_func)),
+        ],
+        ..Default::default()
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = fs::File::create(dir.path().join("stdout")).unwrap();
+    std::io::stdout().flush().unwrap();
+    // SAFETY: `dup` and `dup2` have no memory-safety preconditions.
+    let saved = unsafe { libc::dup(libc::STDOUT_FILENO) };
+    assert!(saved >= 0);
+    assert!(unsafe { libc::dup2(file.as_raw_fd(), libc::STDOUT_FILENO) } >= 0);
+
+    let exit_code = run(env, definition);
+
+    std::io::stdout().flush().unwrap();
+    assert!(unsafe { libc::dup2(saved, libc::STDOUT_FILENO) } >= 0);
+    unsafe { libc::close(saved) };
+    assert_eq!(exit_code, ExitCode::FAILURE);
+
+    let stdout = fs::read_to_string(dir.path().join("stdout")).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout)
+        .unwrap_or_else(|err| panic!("stdout is not JSON ({err}): {stdout}"));
+    let steps = json.as_array().unwrap();
+    assert_eq!(steps.len(), 2);
+    assert_eq!(steps[0]["slug"], "test-1");
+    assert_eq!(steps[0]["passed"], true);
+    assert_eq!(steps[1]["slug"], "test-2");
+    assert_eq!(steps[1]["error"], "fail");
+}
@@ -0,0 +1,36 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Installs the global subscriber, so it lives in its own test binary where no other test can
+//! install one first.
+
+use std::collections::HashMap;
+
+use tracing::Level;
+
+#[test]
+fn test_init_logging_filters_by_level() {
+    let env = HashMap::from([("STACKCLASS_LOG".to_string(), "warn".to_string())]);
+    assert!(tester::init_logging(&env));
+
+    assert!(tracing::enabled!(Level::ERROR));
+    assert!(tracing::enabled!(Level::WARN));
+    assert!(!tracing::enabled!(Level::INFO));
+    assert!(!tracing::enabled!(Level::DEBUG));
+
+    // A second installation is ignored rather than panicking.
+    let env = HashMap::from([("STACKCLASS_DEBUG".to_string(), "true".to_string())]);
+    assert!(!tester::init_logging(&env));
+    assert!(!tracing::enabled!(Level::DEBUG));
+}
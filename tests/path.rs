@@ -0,0 +1,79 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+
+use tester::{TesterError, join_within, resolve_within};
+
+#[test]
+fn test_resolve_within() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+
+    let expected = dir.path().join("src/main.rs").canonicalize().unwrap();
+    assert_eq!(resolve_within(dir.path(), "src/main.rs").unwrap(), expected);
+    assert_eq!(resolve_within(dir.path(), "./src/../src/main.rs").unwrap(), expected);
+
+    let err = resolve_within(dir.path(), "src/missing.rs").unwrap_err();
+    assert!(matches!(err, TesterError::Io(_)), "{err:?}");
+}
+
+#[test]
+fn test_resolve_within_rejects_traversal() {
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path().join("repo");
+    fs::create_dir(&base).unwrap();
+    fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+
+    for relative in ["../secret.txt", "../missing.txt", "a/../../secret.txt", "/etc/passwd"] {
+        let err = resolve_within(&base, relative).unwrap_err();
+        assert!(matches!(err, TesterError::InvalidPath(_)), "{relative}: {err:?}");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_resolve_within_rejects_escaping_symlink() {
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path().join("repo");
+    fs::create_dir(&base).unwrap();
+    fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+    std::os::unix::fs::symlink(dir.path().join("secret.txt"), base.join("link.txt")).unwrap();
+
+    let err = resolve_within(&base, "link.txt").unwrap_err();
+    assert!(matches!(err, TesterError::InvalidPath(_)), "{err:?}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_join_within() {
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path().join("repo");
+    fs::create_dir(&base).unwrap();
+    fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+    std::os::unix::fs::symlink(dir.path().join("secret.txt"), base.join("link.txt")).unwrap();
+
+    let canonical_base = base.canonicalize().unwrap();
+    assert_eq!(join_within(&base, "link.txt").unwrap(), canonical_base.join("link.txt"));
+    assert_eq!(
+        join_within(&base, "./a/../missing.txt").unwrap(),
+        canonical_base.join("missing.txt")
+    );
+
+    for relative in ["../secret.txt", "a/../../secret.txt", "/etc/passwd"] {
+        let err = join_within(&base, relative).unwrap_err();
+        assert!(matches!(err, TesterError::InvalidPath(_)), "{relative}: {err:?}");
+    }
+}
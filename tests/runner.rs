@@ -0,0 +1,542 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use tester::{
+    Case, CaseError, Executable, Harness, Reporter, RunReport, RunSummary, Runner, Step,
+    StepOutcome,
+};
+
+fn pass_func(_harness: &Harness) -> Result<(), CaseError> {
+    Ok(())
+}
+
+fn fail_func(_harness: &Harness) -> Result<(), CaseError> {
+    Err("fail".to_string().into())
+}
+
+fn slow_func(_harness: &Harness) -> Result<(), CaseError> {
+    thread::sleep(Duration::from_millis(300));
+    Ok(())
+}
+
+fn executable() -> Executable {
+    Executable::new(PathBuf::from("examples/echo-tester/your_program.sh")).unwrap()
+}
+
+fn steps(cases: &[Case]) -> Vec<Step<'_>> {
+    cases.iter().map(|case| Step { case, log_prefix: &case.slug, title: &case.slug }).collect()
+}
+
+#[test]
+fn test_hanging_teardown_does_not_wedge_run() {
+    fn hanging_teardown(harness: &Harness) -> Result<(), CaseError> {
+        harness.register_teardown_func(|| thread::sleep(Duration::from_secs(60)));
+        Ok(())
+    }
+
+    let cases =
+        [Case::new("test-1", Arc::new(hanging_teardown)), Case::new("test-2", Arc::new(pass_func))];
+
+    let start = Instant::now();
+    let outcomes = Runner::new(steps(&cases))
+        .with_teardown_timeout(Duration::from_millis(100))
+        .run(false, &executable());
+    assert!(outcomes.iter().all(|outcome| outcome.passed));
+    assert_eq!(outcomes.len(), 2);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_steps_share_random_seed() {
+    use rand::Rng;
+
+    let values = Arc::new(Mutex::new(Vec::new()));
+    let draw = {
+        let values = values.clone();
+        move |harness: &Harness| -> Result<(), CaseError> {
+            values.lock().unwrap().push(harness.rng().random::<u64>());
+            Ok(())
+        }
+    };
+    let draw: tester::Function = Arc::new(draw);
+    let cases = [Case::new("test-1", draw.clone()), Case::new("test-2", draw)];
+
+    let outcomes = Runner::new(steps(&cases)).run(false, &executable());
+    assert!(outcomes.iter().all(|outcome| outcome.passed));
+    let values = values.lock().unwrap();
+    assert_eq!(values.len(), 2);
+    assert_eq!(values[0], values[1]);
+}
+
+#[test]
+fn test_run_parallel_all_pass() {
+    let cases = [
+        Case::new("test-1", Arc::new(slow_func)),
+        Case::new("test-2", Arc::new(slow_func)),
+        Case::new("test-3", Arc::new(slow_func)),
+    ];
+
+    let start = Instant::now();
+    let outcomes = Runner::new(steps(&cases)).run_parallel(false, &executable(), 3);
+    assert!(outcomes.iter().all(|outcome| outcome.passed));
+    assert!(start.elapsed() < Duration::from_millis(800));
+}
+
+#[test]
+fn test_run_parallel_with_failure() {
+    let cases = [
+        Case::new("test-1", Arc::new(pass_func)),
+        Case::new("test-2", Arc::new(fail_func)),
+        Case::new("test-3", Arc::new(pass_func)),
+        Case::new("test-4", Arc::new(slow_func)),
+    ];
+
+    let outcomes = Runner::new(steps(&cases)).run_parallel(false, &executable(), 2);
+    let passed: Vec<_> = outcomes.iter().map(|outcome| outcome.passed).collect();
+    assert_eq!(passed, [true, false, true, true]);
+}
+
+#[test]
+fn test_run_parallel_total_timeout() {
+    let cases = [
+        Case::new("test-1", Arc::new(slow_func)),
+        Case::new("test-2", Arc::new(slow_func)),
+        Case::new("test-3", Arc::new(slow_func)),
+    ];
+
+    let start = Instant::now();
+    let runner = Runner::new(steps(&cases)).with_total_timeout(Duration::from_millis(500));
+    let outcomes = runner.run_parallel(false, &executable(), 1);
+    assert!(start.elapsed() < Duration::from_millis(900));
+
+    let passed: Vec<_> = outcomes.iter().map(|outcome| outcome.passed).collect();
+    assert_eq!(passed, [true, false, false]);
+    let error = outcomes[2].error.as_deref().unwrap();
+    assert_eq!(error, "not run, the run exceeded its total timeout of 500ms");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_case_timeout() {
+    fn run_sleep(harness: &Harness) -> Result<(), CaseError> {
+        harness.new_executable().run(&[])?;
+        Ok(())
+    }
+
+    let executable = Executable::new(PathBuf::from("tests/bin/sleep.sh")).unwrap();
+    let cases = [Case::new("test-1", Arc::new(run_sleep)).with_timeout(Duration::from_secs(1))];
+    assert_eq!(cases[0].default_timeout(), Duration::from_secs(1));
+
+    let start = Instant::now();
+    let outcomes = Runner::new(steps(&cases)).run(false, &executable);
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    assert!(!outcomes[0].passed);
+    assert!(outcomes[0].duration >= Duration::from_secs(1));
+    let error = outcomes[0].error.as_deref().unwrap();
+    assert!(error.starts_with("timed out after "), "{error}");
+    assert!(error.ends_with(" ms, test exceeded 1 seconds"), "{error}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_runner_timeout_caps_case_timeout() {
+    fn run_sleep(harness: &Harness) -> Result<(), CaseError> {
+        harness.new_executable().run(&[])?;
+        Ok(())
+    }
+
+    let executable = Executable::new(PathBuf::from("tests/bin/sleep.sh")).unwrap();
+    let cases = [Case::new("test-1", Arc::new(run_sleep)).with_timeout(Duration::from_secs(30))];
+
+    let start = Instant::now();
+    let runner = Runner::new(steps(&cases)).with_timeout(Duration::from_secs(1));
+    assert!(!runner.run(false, &executable)[0].passed);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_total_timeout_aborts_run() {
+    let cases = [
+        Case::new("test-1", Arc::new(slow_func)),
+        Case::new("test-2", Arc::new(slow_func)),
+        Case::new("test-3", Arc::new(slow_func)),
+        Case::new("test-4", Arc::new(slow_func)),
+    ];
+
+    let start = Instant::now();
+    let runner = Runner::new(steps(&cases)).with_total_timeout(Duration::from_millis(500));
+    let outcomes = runner.run_all(false, &executable());
+    assert!(start.elapsed() < Duration::from_millis(900));
+
+    let passed: Vec<_> = outcomes.iter().map(|outcome| outcome.passed).collect();
+    assert_eq!(passed, [true, false]);
+    let error = outcomes[1].error.as_deref().unwrap();
+    assert!(error.starts_with("interrupted after "), "{error}");
+    assert!(error.ends_with(" ms, the run exceeded its total timeout of 500ms"), "{error}");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_run_cancellable() {
+    let pid = Arc::new(Mutex::new(None));
+    let started = pid.clone();
+    let sleep = move |harness: &Harness| -> Result<(), CaseError> {
+        let mut exe = harness.new_executable();
+        exe.start(&[])?;
+        *started.lock().unwrap() = exe.pid();
+        exe.wait()?;
+        Ok(())
+    };
+    let cases = [Case::new("test-1", Arc::new(sleep)), Case::new("test-2", Arc::new(pass_func))];
+    let executable = Executable::new(PathBuf::from("tests/bin/sleep.sh")).unwrap();
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = cancel.clone();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(300));
+        flag.store(true, Ordering::SeqCst);
+    });
+
+    let start = Instant::now();
+    let outcomes = Runner::new(steps(&cases)).run_cancellable(false, &executable, cancel);
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    assert_eq!(outcomes.len(), 1);
+    assert!(!outcomes[0].passed);
+    let error = outcomes[0].error.as_deref().unwrap();
+    assert!(error.starts_with("cancelled after "), "{error}");
+
+    let pid = pid.lock().unwrap().expect("the process was started");
+    assert!(!PathBuf::from(format!("/proc/{pid}")).exists(), "the process was killed");
+}
+
+#[test]
+fn test_run_outcomes_json() {
+    let cases =
+        [Case::new("test-1", Arc::new(pass_func)), Case::new("test-2", Arc::new(fail_func))];
+
+    let outcomes = Runner::new(steps(&cases)).run(false, &executable());
+    let json = serde_json::to_value(&outcomes).unwrap();
+
+    assert_eq!(json[0]["slug"], "test-1");
+    assert_eq!(json[0]["title"], "test-1");
+    assert_eq!(json[0]["passed"], true);
+    assert!(json[0]["duration_ms"].is_u64());
+    assert!(json[0]["error"].is_null());
+
+    assert_eq!(json[1]["slug"], "test-2");
+    assert_eq!(json[1]["passed"], false);
+    assert_eq!(json[1]["error"], "fail");
+}
+
+#[test]
+fn test_run_reporting() {
+    let cases =
+        [Case::new("test-1", Arc::new(slow_func)), Case::new("test-2", Arc::new(pass_func))];
+
+    let report = Runner::new(steps(&cases)).run_reporting(false, &executable());
+    assert!(report.passed());
+
+    let slugs: Vec<_> = report.steps.iter().map(|step| step.slug.as_str()).collect();
+    assert_eq!(slugs, ["test-1", "test-2"]);
+    assert!(report.steps.iter().all(|step| step.duration > Duration::ZERO));
+    assert!(report.steps[0].duration >= Duration::from_millis(300));
+    assert_eq!(report.total_duration(), report.steps[0].duration + report.steps[1].duration);
+
+    let table = report.timing_table();
+    let rows: Vec<_> = table.lines().collect();
+    assert_eq!(rows.len(), 4);
+    assert!(rows[1].starts_with("test-1  passed"));
+    assert!(rows[2].starts_with("test-2  passed"));
+    assert!(rows[3].starts_with("total"));
+}
+
+#[test]
+fn test_skip_step_with_failed_dependency() {
+    let cases = [
+        Case::new("stage-1", Arc::new(fail_func)),
+        Case::new("stage-2", Arc::new(pass_func)).with_dependency("stage-1"),
+        Case::new("stage-3", Arc::new(pass_func)),
+    ];
+
+    let outcomes = Runner::new(steps(&cases)).run_all(false, &executable());
+    let results: Vec<_> =
+        outcomes.iter().map(|outcome| (outcome.passed, outcome.skipped)).collect();
+    assert_eq!(results, [(false, false), (false, true), (true, false)]);
+    assert_eq!(outcomes[1].error.as_deref(), Some("skipped due to failed dependency stage-1"));
+
+    let report = RunReport { steps: outcomes };
+    assert!(report.timing_table().lines().nth(2).unwrap().starts_with("stage-2  skipped"));
+}
+
+#[test]
+fn test_skip_if_predicate() {
+    fn should_skip(env: &HashMap<String, String>) -> bool {
+        env.get("SKIP_SLOW").is_some_and(|value| value == "1")
+    }
+
+    let cases = [
+        Case::new("stage-1", Arc::new(fail_func)).with_skip_if(Arc::new(should_skip)),
+        Case::new("stage-2", Arc::new(pass_func)),
+    ];
+
+    let env = HashMap::from([("SKIP_SLOW".to_string(), "1".to_string())]);
+    let outcomes = Runner::new(steps(&cases)).with_env(Arc::new(env)).run(false, &executable());
+    let results: Vec<_> =
+        outcomes.iter().map(|outcome| (outcome.passed, outcome.skipped)).collect();
+    assert_eq!(results, [(true, true), (true, false)]);
+    assert_eq!(outcomes[0].error.as_deref(), Some("skipped because its skip condition holds"));
+
+    let report = RunReport { steps: outcomes };
+    assert!(report.passed());
+    assert!(report.timing_table().lines().nth(1).unwrap().starts_with("stage-1  skipped"));
+
+    let outcomes = Runner::new(steps(&cases)).run(false, &executable());
+    assert!(!outcomes[0].passed);
+    assert!(!outcomes[0].skipped);
+}
+
+#[test]
+fn test_run_summary() {
+    let cases = [
+        Case::new("test-1", Arc::new(pass_func)),
+        Case::new("test-2", Arc::new(fail_func)),
+        Case::new("test-3", Arc::new(pass_func)).with_dependency("test-2"),
+        Case::new("test-4", Arc::new(pass_func)),
+        Case::new("test-5", Arc::new(pass_func)).with_skip_if(Arc::new(|_| true)),
+    ];
+
+    let report = RunReport { steps: Runner::new(steps(&cases)).run_all(false, &executable()) };
+    let summary = report.summary();
+    assert_eq!(summary, RunSummary { total: 5, passed: 2, failed: 1, skipped: 2 });
+    assert_eq!(summary.to_string(), "2 of 5 steps passed (1 failed, 2 skipped)");
+}
+
+/// Records the callbacks it receives, in order.
+#[derive(Default)]
+struct RecordingReporter(Mutex<Vec<String>>);
+
+impl Reporter for RecordingReporter {
+    fn on_step_start(&self, step: &Step<'_>) {
+        self.0.lock().unwrap().push(format!("start {}", step.case.slug));
+    }
+
+    fn on_step_pass(&self, outcome: &StepOutcome) {
+        self.0.lock().unwrap().push(format!("pass {}", outcome.slug));
+    }
+
+    fn on_step_fail(&self, outcome: &StepOutcome, err: &str) {
+        self.0.lock().unwrap().push(format!("fail {}: {err}", outcome.slug));
+    }
+
+    fn on_step_skip(&self, outcome: &StepOutcome, reason: &str) {
+        self.0.lock().unwrap().push(format!("skip {}: {reason}", outcome.slug));
+    }
+
+    fn on_run_complete(&self, outcomes: &[StepOutcome]) {
+        self.0.lock().unwrap().push(format!("complete {}", outcomes.len()));
+    }
+}
+
+#[test]
+fn test_reporter_callbacks() {
+    let cases = [
+        Case::new("test-1", Arc::new(pass_func)),
+        Case::new("test-2", Arc::new(fail_func)),
+        Case::new("test-3", Arc::new(pass_func)).with_dependency("test-2"),
+    ];
+
+    let reporter = Arc::new(RecordingReporter::default());
+    Runner::new(steps(&cases)).with_reporter(reporter.clone()).run_all(false, &executable());
+
+    assert_eq!(
+        *reporter.0.lock().unwrap(),
+        [
+            "start test-1",
+            "pass test-1",
+            "start test-2",
+            "fail test-2: fail",
+            "start test-3",
+            "skip test-3: skipped due to failed dependency test-2",
+            "complete 3",
+        ]
+    );
+}
+
+#[test]
+fn test_harness_receives_log_prefix() {
+    fn check_prefix(harness: &Harness) -> Result<(), CaseError> {
+        match harness.logger().prefix() {
+            "test-1" => Ok(()),
+            prefix => Err(format!("unexpected log prefix {prefix:?}").into()),
+        }
+    }
+
+    let cases = [Case::new("test-1", Arc::new(check_prefix))];
+    assert!(Runner::new(steps(&cases)).run(false, &executable())[0].passed);
+}
+
+#[test]
+fn test_harness_receives_env() {
+    fn check_seed(harness: &Harness) -> Result<(), CaseError> {
+        match harness.env("STACKCLASS_RANDOM_SEED") {
+            Some("42") => Ok(()),
+            seed => Err(format!("unexpected seed {seed:?}").into()),
+        }
+    }
+
+    let cases = [Case::new("test-1", Arc::new(check_seed))];
+    let env = HashMap::from([("STACKCLASS_RANDOM_SEED".to_string(), "42".to_string())]);
+
+    assert!(Runner::new(steps(&cases)).with_env(Arc::new(env)).run(false, &executable())[0].passed);
+    assert!(!Runner::new(steps(&cases)).run(false, &executable())[0].passed);
+}
+
+#[test]
+fn test_setup_runs_before_case() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+
+    let setup_events = events.clone();
+    let case_events = events.clone();
+    let case = Case::new(
+        "test-1",
+        Arc::new(move |_: &Harness| {
+            case_events.lock().unwrap().push("case");
+            Ok(())
+        }),
+    )
+    .with_setup(Arc::new(move |harness: &Harness| {
+        setup_events.lock().unwrap().push("setup");
+        let teardown_events = setup_events.clone();
+        harness.register_teardown_func(move || teardown_events.lock().unwrap().push("teardown"));
+        Ok(())
+    }));
+
+    let cases = [case];
+    assert!(Runner::new(steps(&cases)).run(false, &executable())[0].passed);
+    assert_eq!(*events.lock().unwrap(), ["setup", "case", "teardown"]);
+}
+
+#[test]
+fn test_setup_failure_skips_case() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+
+    let setup_events = events.clone();
+    let case_events = events.clone();
+    let case = Case::new(
+        "test-1",
+        Arc::new(move |_: &Harness| {
+            case_events.lock().unwrap().push("case");
+            Ok(())
+        }),
+    )
+    .with_setup(Arc::new(move |harness: &Harness| {
+        let teardown_events = setup_events.clone();
+        harness.register_teardown_func(move || teardown_events.lock().unwrap().push("teardown"));
+        Err("fixture missing".into())
+    }));
+
+    let cases = [case];
+    let outcomes = Runner::new(steps(&cases)).run(false, &executable());
+    assert!(!outcomes[0].passed);
+    assert_eq!(outcomes[0].error.as_deref(), Some("setup failed: fixture missing"));
+    assert_eq!(*events.lock().unwrap(), ["teardown"]);
+}
+
+#[test]
+fn test_run_all_continues_past_failures() {
+    let cases = [
+        Case::new("test-1", Arc::new(pass_func)),
+        Case::new("test-2", Arc::new(fail_func)),
+        Case::new("test-3", Arc::new(pass_func)),
+        Case::new("test-4", Arc::new(fail_func)),
+    ];
+
+    let runner = Runner::new(steps(&cases));
+
+    let outcomes = runner.run_all(false, &executable());
+    let passed: Vec<_> =
+        outcomes.iter().map(|outcome| (outcome.slug.as_str(), outcome.passed)).collect();
+    assert_eq!(passed, [("test-1", true), ("test-2", false), ("test-3", true), ("test-4", false)]);
+
+    // `run` stops at the first failure.
+    assert_eq!(runner.run(false, &executable()).len(), 2);
+}
+
+#[test]
+fn test_retries_flaky_case() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let teardowns = Arc::new(AtomicUsize::new(0));
+    let flaky = {
+        let attempts = attempts.clone();
+        let teardowns = teardowns.clone();
+        move |harness: &Harness| -> Result<(), CaseError> {
+            let teardowns = teardowns.clone();
+            harness.register_teardown_func(move || {
+                teardowns.fetch_add(1, Ordering::SeqCst);
+            });
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 => Err("flaked".to_string().into()),
+                _ => Ok(()),
+            }
+        }
+    };
+
+    let cases = [Case::new("test-1", Arc::new(flaky.clone())).with_retries(1)];
+    assert!(Runner::new(steps(&cases)).run(false, &executable())[0].passed);
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    assert_eq!(teardowns.load(Ordering::SeqCst), 2);
+
+    attempts.store(0, Ordering::SeqCst);
+    let cases = [Case::new("test-1", Arc::new(flaky))];
+    let outcomes = Runner::new(steps(&cases)).run(false, &executable());
+    assert_eq!(outcomes[0].error.as_deref(), Some("flaked"));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_case_env_overrides() {
+    fn check_feature(harness: &Harness, expected: &str) -> Result<(), CaseError> {
+        let (stdout, _, _) = harness.new_executable().run(&["FEATURE"])?;
+        match String::from_utf8_lossy(&stdout).trim_end() {
+            line if line == format!("FEATURE={expected}") => Ok(()),
+            line => Err(format!("unexpected output {line:?}").into()),
+        }
+    }
+
+    let env = |value: &str| HashMap::from([("FEATURE".to_string(), value.to_string())]);
+    let cases = [
+        Case::new("test-1", Arc::new(|harness: &Harness| check_feature(harness, "off")))
+            .with_env(env("off")),
+        Case::new("test-2", Arc::new(|harness: &Harness| check_feature(harness, "on")))
+            .with_env(env("on")),
+    ];
+
+    let executable = Executable::new(PathBuf::from("tests/bin/env.sh")).unwrap();
+    let outcomes = Runner::new(steps(&cases)).run(false, &executable);
+    assert!(outcomes.iter().all(|outcome| outcome.passed), "{outcomes:?}");
+}
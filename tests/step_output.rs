@@ -0,0 +1,178 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Redirects this process's stdout and installs the global subscriber, so these tests live in
+//! their own test binary where no other test can interfere.
+
+#![cfg(unix)]
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::Write,
+    os::fd::AsRawFd,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use tester::{Case, CaseError, Executable, Harness, Runner, Step};
+
+fn echo_func(harness: &Harness) -> Result<(), CaseError> {
+    harness.logger().infof("starting the program");
+
+    let mut exe = harness.new_executable();
+    exe.start(&["program output"])?;
+    exe.wait()?;
+    Ok(())
+}
+
+/// Serializes the tests, which all redirect the process's stdout.
+static STDOUT: Mutex<()> = Mutex::new(());
+
+/// Runs `f` with the process's stdout redirected to a file, returning what was written to it.
+fn capture_stdout(f: impl FnOnce()) -> String {
+    let _guard = STDOUT.lock().unwrap();
+    tester::init_logging(&HashMap::new());
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = fs::File::create(dir.path().join("stdout")).unwrap();
+
+    std::io::stdout().flush().unwrap();
+    // SAFETY: `dup` and `dup2` have no memory-safety preconditions.
+    let saved = unsafe { libc::dup(libc::STDOUT_FILENO) };
+    assert!(saved >= 0);
+    assert!(unsafe { libc::dup2(file.as_raw_fd(), libc::STDOUT_FILENO) } >= 0);
+
+    f();
+
+    std::io::stdout().flush().unwrap();
+    assert!(unsafe { libc::dup2(saved, libc::STDOUT_FILENO) } >= 0);
+    unsafe { libc::close(saved) };
+
+    fs::read_to_string(dir.path().join("stdout")).unwrap()
+}
+
+#[test]
+fn test_buffered_output_brackets_step() {
+    let case = Case::new("echo", Arc::new(echo_func));
+    let executable = Executable::new(PathBuf::from("tests/bin/echo.sh")).unwrap().with_tee(true);
+
+    let mut outcomes = Vec::new();
+    let output = capture_stdout(|| {
+        let steps = vec![Step { case: &case, log_prefix: "stage-1", title: "Stage #1: Echo" }];
+        outcomes = Runner::new(steps).with_buffered_output(true).run(false, &executable);
+    });
+    assert!(outcomes[0].passed);
+
+    assert!(output.starts_with("=== begin echo ===\n"), "{output}");
+    // Only the run summary follows the step's block.
+    let (_, after) = output.split_once("=== end echo ===\n").unwrap();
+    assert!(after.trim_start().lines().count() == 1, "{output}");
+    assert!(after.contains("1 of 1 steps passed (0 failed, 0 skipped)"), "{output}");
+
+    let running = output.find("Running tests for Stage #1: Echo").unwrap();
+    let starting = output.find("[stage-1] starting the program").unwrap();
+    let program = output.find("program output\n").unwrap();
+    let passed = output.find("Test passed in ").unwrap();
+    assert!(running < starting && starting < program && program < passed, "{output}");
+}
+
+#[test]
+fn test_buffered_output_is_not_colored_off_terminal() {
+    fn fail_func(_harness: &Harness) -> Result<(), CaseError> {
+        Err("fail".to_string().into())
+    }
+
+    let cases = [
+        Case::new("test-1", Arc::new(echo_func)),
+        Case::new("test-2", Arc::new(fail_func)),
+        Case::new("test-3", Arc::new(echo_func)).with_dependency("test-2"),
+    ];
+    let executable = Executable::new(PathBuf::from("tests/bin/echo.sh")).unwrap();
+
+    // Stdout is a file here, not a terminal, so no colors are used.
+    let output = capture_stdout(|| {
+        let steps = cases
+            .iter()
+            .map(|case| Step { case, log_prefix: &case.slug, title: &case.slug })
+            .collect();
+        Runner::new(steps).with_buffered_output(true).run_all(false, &executable);
+    });
+    assert!(output.contains("Test skipped due to failed dependency test-2."), "{output}");
+    assert!(!output.contains('\x1b'), "{output}");
+}
+
+#[test]
+fn test_failure_shows_description() {
+    fn fail_func(_harness: &Harness) -> Result<(), CaseError> {
+        Err("expected +PONG".to_string().into())
+    }
+
+    let case = Case::new("ping", Arc::new(fail_func))
+        .with_description("the server replies to PING with +PONG");
+    let executable = Executable::new(PathBuf::from("tests/bin/echo.sh")).unwrap();
+
+    let mut outcomes = Vec::new();
+    let output = capture_stdout(|| {
+        let steps = vec![Step { case: &case, log_prefix: "stage-1", title: "Stage #1: Ping" }];
+        outcomes = Runner::new(steps).with_buffered_output(true).run(false, &executable);
+    });
+    assert_eq!(outcomes[0].description.as_deref(), Some("the server replies to PING with +PONG"));
+
+    let error = output.find("expected +PONG").unwrap();
+    let description = output.find("Stage verifies: the server replies to PING with +PONG").unwrap();
+    assert!(error < description, "{output}");
+}
+
+#[test]
+fn test_parallel_output_is_not_interleaved() {
+    fn slow_echo(harness: &Harness) -> Result<(), CaseError> {
+        let logger = harness.logger();
+        logger.infof("first log line");
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        let mut exe = harness.new_executable();
+        exe.start(&[logger.prefix()])?;
+        exe.wait()?;
+        logger.infof("last log line");
+        Ok(())
+    }
+
+    let cases =
+        [Case::new("test-1", Arc::new(slow_echo)), Case::new("test-2", Arc::new(slow_echo))];
+    let executable = Executable::new(PathBuf::from("tests/bin/echo.sh")).unwrap().with_tee(true);
+
+    let output = capture_stdout(|| {
+        let steps = cases
+            .iter()
+            .map(|case| Step { case, log_prefix: &case.slug, title: &case.slug })
+            .collect();
+        let outcomes =
+            Runner::new(steps).with_buffered_output(true).run_parallel(false, &executable, 2);
+        assert!(outcomes.iter().all(|outcome| outcome.passed));
+    });
+
+    let (first, second) = output.split_once("=== end test-1 ===\n").unwrap();
+    assert!(first.starts_with("=== begin test-1 ===\n"), "{output}");
+    assert!(second.trim_start().starts_with("=== begin test-2 ===\n"), "{output}");
+    assert!(!first.contains("test-2"), "{output}");
+    for block in [first, second] {
+        let running = block.find("Running tests for").unwrap();
+        let starting = block.find("first log line").unwrap();
+        let program = block.find("\ntest-").unwrap();
+        let finished = block.find("last log line").unwrap();
+        let passed = block.find("Test passed in").unwrap();
+        assert!(running < starting && starting < program, "{output}");
+        assert!(program < finished && finished < passed, "{output}");
+    }
+}
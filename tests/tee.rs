@@ -0,0 +1,55 @@
+// Copyright (c) The StackClass Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Redirects this process's stdout, so it lives in its own test binary where no other test can
+//! write to stdout at the same time.
+
+#![cfg(unix)]
+
+use std::{fs, io::Write, os::fd::AsRawFd, path::PathBuf, time::Duration};
+
+use tester::Executable;
+
+#[test]
+fn test_tee_forwards_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = fs::File::create(dir.path().join("stdout")).unwrap();
+
+    std::io::stdout().flush().unwrap();
+    // SAFETY: `dup` and `dup2` have no memory-safety preconditions.
+    let saved = unsafe { libc::dup(libc::STDOUT_FILENO) };
+    assert!(saved >= 0);
+    assert!(unsafe { libc::dup2(file.as_raw_fd(), libc::STDOUT_FILENO) } >= 0);
+
+    let mut exe = Executable::new(PathBuf::from("tests/bin/ticker.sh")).unwrap().with_tee(true);
+    exe.start(&[]).unwrap();
+    let captured = exe.read_until(b"two\n", Duration::from_secs(5));
+    exe.kill().unwrap();
+
+    // Output past the limit is neither kept nor forwarded.
+    let mut flood = Executable::new(PathBuf::from("tests/bin/yes.sh"))
+        .unwrap()
+        .with_tee(true)
+        .with_max_output_bytes(1000);
+    let flooded = flood.run(&[]);
+
+    std::io::stdout().flush().unwrap();
+    assert!(unsafe { libc::dup2(saved, libc::STDOUT_FILENO) } >= 0);
+    unsafe { libc::close(saved) };
+
+    assert_eq!(captured.unwrap(), b"one\ntwo\n");
+    assert_eq!(flooded.unwrap().0, b"y\n".repeat(500));
+    let forwarded = fs::read(dir.path().join("stdout")).unwrap();
+    assert_eq!(forwarded, [b"one\ntwo\n".to_vec(), b"y\n".repeat(500)].concat());
+}